@@ -24,6 +24,13 @@ fn setup(mut cmds: Commands) {
     //     Field::Torus(Torus::new(0.3, 0.5)),
     //     Transform::from_xyz(0.0, -1.0, 0.0),
     // ));
+    // cmds.spawn((
+    //     Field::Composite(CompositeField::subtraction([
+    //         Field::Cuboid(Cuboid::new(1., 0.1, 1.)).with_transform(Transform::IDENTITY),
+    //         Field::Cylinder(Cylinder::new(0.2, 0.5)).with_transform(Transform::IDENTITY),
+    //     ])),
+    //     Transform::from_xyz(0.0, -1.0, 0.0),
+    // ));
     cmds.spawn((
         Field::Cylinder(Cylinder::new(0.3, 0.5)),
         Transform::from_xyz(0.0, -1.0, 0.0),
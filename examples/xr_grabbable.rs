@@ -24,6 +24,7 @@ use bevy_suis::{
     handler_actions::single::SingleHandlerAction,
     input_handler::{FieldRef, InputHandler},
     input_method_data::SpatialInputData,
+    shape_cast::ShapeCast,
 };
 use openxr::ReferenceSpaceType;
 
@@ -97,7 +98,10 @@ fn move_grabble(
         {
             let offset = match actor.spatial_data {
                 SpatialInputData::Hand(_) | SpatialInputData::Tip(_) => Vec3A::ZERO,
-                SpatialInputData::Ray(_) => {
+                SpatialInputData::Ray(_)
+                | SpatialInputData::FiniteRay { .. }
+                | SpatialInputData::Arc(_)
+                | SpatialInputData::Cast(_) => {
                     Vec3A::NEG_Z * actor.non_spatial_data.scroll.unwrap_or_default().y
                 }
             };
@@ -115,9 +119,14 @@ fn iso_from_spatial_data(spatial_data: SpatialInputData) -> Isometry3d {
     match spatial_data {
         SpatialInputData::Hand(hand) => Isometry3d::new(hand.palm.pos, hand.palm.rot),
         SpatialInputData::Tip(iso) => iso,
-        SpatialInputData::Ray(ray) => Transform::from_translation(ray.origin)
+        SpatialInputData::Ray(ray)
+        | SpatialInputData::FiniteRay { ray, .. }
+        | SpatialInputData::Cast(ShapeCast { ray, .. }) => Transform::from_translation(ray.origin)
             .looking_to(ray.direction, Dir3::Y)
             .to_isometry(),
+        SpatialInputData::Arc(arc) => Transform::from_translation(arc.origin)
+            .looking_to(arc.velocity, Dir3::Y)
+            .to_isometry(),
     }
 }
 
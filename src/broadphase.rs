@@ -0,0 +1,94 @@
+use bevy::{math::bounding::Aabb3d, prelude::*};
+
+use crate::{
+    bvh::{Bvh, BvhNode},
+    field::FieldCache,
+};
+
+/// How far away handlers are still considered when ordering them for an input method
+#[derive(Resource, Debug, Clone, Copy, PartialEq)]
+pub struct SuisBroadphaseSettings {
+    /// handlers with fields further away than this are left out of the handler order of an input
+    /// method, without computing their exact distance, and don't get any
+    /// [`InputData`](crate::input_method_data::InputData) from it either. `f32::INFINITY` keeps
+    /// every handler.
+    pub cull_distance: f32,
+}
+
+impl Default for SuisBroadphaseSettings {
+    fn default() -> Self {
+        Self {
+            cull_distance: 10.0,
+        }
+    }
+}
+
+/// A bounding volume hierarchy over the world-space bounds of every [`Field`](crate::field::Field),
+/// rebuilt whenever a field moves or changes
+#[derive(Resource, Debug, Default)]
+pub struct FieldBroadphase {
+    bvh: Bvh<Entity>,
+    /// fields without bounds, these can't be culled
+    unbounded: Vec<Entity>,
+}
+
+impl FieldBroadphase {
+    pub fn build(fields: impl IntoIterator<Item = (Entity, Option<Aabb3d>)>) -> Self {
+        let mut bounded = Vec::new();
+        let mut unbounded = Vec::new();
+        for (field, bounds) in fields {
+            match bounds {
+                Some(bounds) => bounded.push((field, bounds)),
+                None => unbounded.push(field),
+            }
+        }
+        Self {
+            bvh: Bvh::build(bounded),
+            unbounded,
+        }
+    }
+    /// every field that might be closer than `max_distance`. `lower_bound` has to never be more
+    /// than the distance to anything inside of the bounds it gets passed, whole branches of the
+    /// hierarchy further away than `max_distance` get skipped. Unbounded fields are always
+    /// included.
+    pub fn candidates(
+        &self,
+        lower_bound: impl Fn(&Aabb3d) -> f32,
+        max_distance: f32,
+    ) -> Vec<Entity> {
+        let mut candidates = self.unbounded.clone();
+        let nodes = self.bvh.nodes();
+        let mut stack = Vec::new();
+        if !nodes.is_empty() {
+            stack.push(0);
+        }
+        while let Some(index) = stack.pop() {
+            let node = &nodes[index];
+            if lower_bound(node.bounds()) > max_distance {
+                continue;
+            }
+            match node {
+                BvhNode::Leaf { item, .. } => candidates.push(*item),
+                BvhNode::Branch { children, .. } => stack.extend(children),
+            }
+        }
+        candidates
+    }
+}
+
+pub(crate) fn rebuild_field_broadphase(
+    changed: Query<(), Changed<FieldCache>>,
+    mut removed: RemovedComponents<FieldCache>,
+    fields: Query<(Entity, &FieldCache)>,
+    mut broadphase: ResMut<FieldBroadphase>,
+) {
+    let any_removed = removed.read().count() > 0;
+    if changed.is_empty() && !any_removed {
+        return;
+    }
+    *broadphase = FieldBroadphase::build(
+        fields
+            .iter()
+            .map(|(field, cache)| (field, cache.world_bounds())),
+    );
+}
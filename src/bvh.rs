@@ -0,0 +1,92 @@
+use bevy::math::{
+    Vec3A,
+    bounding::{Aabb3d, BoundingVolume},
+};
+
+/// A bounding volume hierarchy with one item per leaf. The root is at index 0 and children always
+/// come after their parent.
+#[derive(Debug, Clone)]
+pub(crate) struct Bvh<T> {
+    nodes: Vec<BvhNode<T>>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum BvhNode<T> {
+    Leaf {
+        bounds: Aabb3d,
+        item: T,
+    },
+    Branch {
+        bounds: Aabb3d,
+        children: [usize; 2],
+    },
+}
+
+impl<T> BvhNode<T> {
+    pub fn bounds(&self) -> &Aabb3d {
+        match self {
+            BvhNode::Leaf { bounds, .. } | BvhNode::Branch { bounds, .. } => bounds,
+        }
+    }
+}
+
+impl<T> Default for Bvh<T> {
+    fn default() -> Self {
+        Self { nodes: Vec::new() }
+    }
+}
+
+impl<T: Copy> Bvh<T> {
+    pub fn build(mut items: Vec<(T, Aabb3d)>) -> Self {
+        let mut bvh = Self::default();
+        if !items.is_empty() {
+            bvh.build_node(&mut items);
+        }
+        bvh
+    }
+    /// returns the index of the node, `items` can't be empty
+    fn build_node(&mut self, items: &mut [(T, Aabb3d)]) -> usize {
+        let index = self.nodes.len();
+        if let [(item, bounds)] = *items {
+            self.nodes.push(BvhNode::Leaf { bounds, item });
+            return index;
+        }
+        let bounds = items
+            .iter()
+            .map(|(_, bounds)| *bounds)
+            .reduce(|a, b| a.merge(&b))
+            .unwrap();
+        // reserve the spot so the root always ends up at 0
+        self.nodes.push(BvhNode::Branch {
+            bounds,
+            children: [0, 0],
+        });
+        // split at the median along the axis the centers are spread out the most on
+        let (min, max) = items.iter().fold(
+            (Vec3A::INFINITY, Vec3A::NEG_INFINITY),
+            |(min, max), (_, bounds)| (min.min(bounds.center()), max.max(bounds.center())),
+        );
+        let extent = max - min;
+        let axis = if extent.x >= extent.y && extent.x >= extent.z {
+            0
+        } else if extent.y >= extent.z {
+            1
+        } else {
+            2
+        };
+        let middle = items.len() / 2;
+        items.select_nth_unstable_by(middle, |(_, a), (_, b)| {
+            a.center()[axis].total_cmp(&b.center()[axis])
+        });
+        let (left, right) = items.split_at_mut(middle);
+        let children = [self.build_node(left), self.build_node(right)];
+        self.nodes[index] = BvhNode::Branch { bounds, children };
+        index
+    }
+}
+
+impl<T> Bvh<T> {
+    pub fn nodes(&self) -> &[BvhNode<T>] {
+        &self.nodes
+    }
+}
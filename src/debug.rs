@@ -1,8 +1,18 @@
-use bevy::{color::palettes::css, prelude::*};
+use std::f32::consts::{FRAC_PI_2, TAU};
+
+use bevy::{
+    color::palettes::css,
+    ecs::entity::EntityHashMap,
+    math::bounding::{Aabb3d, BoundingVolume},
+    prelude::*,
+};
 
 use crate::{
-    InputMethodDisabled, field::Field, input_method::InputMethod,
+    InputMethodDisabled,
+    field::{CsgOperation, Field},
+    input_method::InputMethod,
     input_method_data::SpatialInputData,
+    raymarching::SuisRaymarchSettings,
 };
 pub struct SuisDebugGizmosPlugin;
 
@@ -39,25 +49,278 @@ fn draw_method_gizmos(
             SpatialInputData::Ray(ray) => {
                 gizmos.line(ray.origin, ray.origin + (*ray.direction * 0.2), color);
             }
+            SpatialInputData::FiniteRay { ray, max_length } => {
+                gizmos.line(ray.origin, ray.get_point(*max_length), color);
+            }
+            SpatialInputData::Arc(arc) => {
+                gizmos.linestrip(arc.points(), color);
+            }
+            SpatialInputData::Cast(cast) => {
+                const LENGTH: f32 = 0.2;
+                let end = cast.ray.get_point(LENGTH);
+                let rotation = Quat::from_rotation_arc(Vec3::Z, *cast.ray.direction);
+                gizmos.line(cast.ray.origin, end, color);
+                gizmos.circle(
+                    Isometry3d::new(end, rotation),
+                    cast.radius_at(LENGTH),
+                    color,
+                );
+                for side in [Vec3::X, Vec3::NEG_X, Vec3::Y, Vec3::NEG_Y] {
+                    gizmos.line(
+                        cast.ray.origin + rotation * side * cast.radius_at(0.0),
+                        end + rotation * side * cast.radius_at(LENGTH),
+                        color,
+                    );
+                }
+            }
         }
     }
 }
 
-fn draw_fields(field_query: Query<(&GlobalTransform, &Field)>, mut gizmos: Gizmos) {
-    for (transform, field) in &field_query {
-        match field {
-            Field::Sphere(r) => {
-                gizmos.sphere(transform.to_isometry(), *r, css::LIME);
+/// the traced contours of a field in its local space, each with the color to draw it in
+type FieldContours = Vec<(Srgba, Vec<Option<Vec3>>)>;
+
+fn draw_fields(
+    field_query: Query<(Entity, &GlobalTransform, Ref<Field>)>,
+    mut removed: RemovedComponents<Field>,
+    mut contours: Local<EntityHashMap<FieldContours>>,
+    mut gizmos: Gizmos,
+) {
+    for entity in removed.read() {
+        contours.remove(&entity);
+    }
+    for (entity, transform, field) in &field_query {
+        draw_field(&mut gizmos, &field, transform, css::LIME);
+        // tracing is too slow to redo every frame, so only when the field changed
+        if field.is_changed() || !contours.contains_key(&entity) {
+            let mut traced = FieldContours::new();
+            trace_field_contours(&field, &GlobalTransform::IDENTITY, css::LIME, &mut traced);
+            contours.insert(entity, traced);
+        }
+        for (color, points) in &contours[&entity] {
+            for pair in points.windows(2) {
+                if let [Some(start), Some(end)] = *pair {
+                    gizmos.line(
+                        transform.transform_point(start),
+                        transform.transform_point(end),
+                        *color,
+                    );
+                }
+            }
+        }
+    }
+}
+
+fn draw_field(gizmos: &mut Gizmos, field: &Field, transform: &GlobalTransform, color: Srgba) {
+    match field {
+        Field::Sphere(r) => {
+            gizmos.sphere(transform.to_isometry(), *r, color);
+        }
+        Field::Cuboid(cuboid) => gizmos.primitive_3d(cuboid, transform.to_isometry(), color),
+        Field::Torus(torus) => {
+            gizmos.primitive_3d(torus, transform.to_isometry(), color);
+        }
+        Field::Cylinder(cylinder) => {
+            gizmos.primitive_3d(cylinder, transform.to_isometry(), color);
+        }
+        Field::RoundedCuboid(rounded_cuboid) => {
+            let half_size = rounded_cuboid.half_size;
+            let inner = rounded_cuboid.inner_cuboid().half_size;
+            let radius = rounded_cuboid.radius.clamp(0.0, half_size.min_element());
+            // the rounded cross sections through the center and the flat part of every side, the
+            // rotations turn the XY plane of the rects into each plane of the cuboid
+            for (rotation, [x, y, z]) in [
+                (Quat::IDENTITY, [0, 1, 2]),
+                (Quat::from_rotation_y(FRAC_PI_2), [2, 1, 0]),
+                (Quat::from_rotation_x(FRAC_PI_2), [0, 2, 1]),
+            ] {
+                let isometry = transform.to_isometry() * Isometry3d::from_rotation(rotation);
+                gizmos
+                    .rounded_rect(isometry, Vec2::new(half_size[x], half_size[y]) * 2.0, color)
+                    .corner_radius(radius);
+                for side in [-1.0, 1.0] {
+                    let offset = Isometry3d::from_translation(Vec3::Z * half_size[z] * side);
+                    gizmos.rect(
+                        isometry * offset,
+                        Vec2::new(inner[x], inner[y]) * 2.0,
+                        color,
+                    );
+                }
+            }
+        }
+        Field::Capsule(capsule) => {
+            gizmos.primitive_3d(capsule, transform.to_isometry(), color);
+        }
+        Field::Cone(cone) => {
+            gizmos.primitive_3d(cone, transform.to_isometry(), color);
+        }
+        Field::ConicalFrustum(frustum) => {
+            gizmos.primitive_3d(frustum, transform.to_isometry(), color);
+        }
+        Field::Plane(plane) => {
+            // the plane gizmo only draws a fixed size grid, the rect is drawn in the XY plane while
+            // the plane spans X and Z before being rotated to its normal
+            let rotation = Quat::from_rotation_arc(Vec3::Y, plane.normal.as_vec3())
+                * Quat::from_rotation_x(FRAC_PI_2);
+            let mut isometry = transform.to_isometry();
+            isometry.rotation *= rotation;
+            gizmos.rect(isometry, plane.half_size * 2.0, color);
+        }
+        Field::Panel(panel) => {
+            gizmos
+                .rounded_rect(transform.to_isometry(), panel.half_size * 2.0, color)
+                .corner_radius(panel.corner_radius.max(0.0));
+        }
+        Field::CurvedPanel(panel) => {
+            const RESOLUTION: usize = 32;
+            let half_angle = panel.half_angle();
+            let half_height = panel.height / 2.0;
+            let radii = [
+                panel.radius - panel.thickness / 2.0,
+                panel.radius + panel.thickness / 2.0,
+            ];
+            let point =
+                |angle, radius, y| transform.transform_point(panel.point(angle, radius, y).into());
+            for radius in radii {
+                for y in [-half_height, half_height] {
+                    gizmos.linestrip(
+                        (0..=RESOLUTION).map(|i| {
+                            let angle = (i as f32 / RESOLUTION as f32 * 2.0 - 1.0) * half_angle;
+                            point(angle, radius, y)
+                        }),
+                        color,
+                    );
+                }
             }
-            Field::Cuboid(cuboid) => {
-                gizmos.primitive_3d(cuboid, transform.to_isometry(), css::LIME)
+            // the outline of both ends
+            for angle in [-half_angle, half_angle] {
+                gizmos.linestrip(
+                    [
+                        (radii[0], -half_height),
+                        (radii[1], -half_height),
+                        (radii[1], half_height),
+                        (radii[0], half_height),
+                        (radii[0], -half_height),
+                    ]
+                    .map(|(radius, y)| point(angle, radius, y)),
+                    color,
+                );
             }
-            Field::Torus(torus) => {
-                gizmos.primitive_3d(torus, transform.to_isometry(), css::LIME);
+        }
+        Field::InfinitePlane(plane) => {
+            gizmos.primitive_3d(
+                &Plane3d {
+                    normal: plane.normal,
+                    half_size: Vec2::ONE,
+                },
+                transform.to_isometry(),
+                color,
+            );
+        }
+        Field::Triangle(triangle) => {
+            gizmos.primitive_3d(triangle, transform.to_isometry(), color);
+        }
+        Field::Tetrahedron(tetrahedron) => {
+            gizmos.primitive_3d(tetrahedron, transform.to_isometry(), color);
+        }
+        Field::Segment(segment) => {
+            gizmos.primitive_3d(segment, transform.to_isometry(), color);
+        }
+        // only the bounds, the mesh itself is usually visible anyway
+        Field::Mesh(_) | Field::Custom(_) => {
+            if let Some(bounds) = field.local_bounds() {
+                let bounds = Transform::from_translation(bounds.center().into())
+                    .with_scale((bounds.max - bounds.min).into());
+                gizmos.cuboid(transform.mul_transform(bounds), color);
             }
-            Field::Cylinder(cylinder) => {
-                gizmos.primitive_3d(cylinder, transform.to_isometry(), css::LIME);
+        }
+        Field::Composite(composite) => {
+            for (i, child) in composite.children.iter().enumerate() {
+                // the parts cut out of a subtraction get their own color
+                let color = match composite.operation {
+                    CsgOperation::Subtraction if i > 0 => css::ORANGE_RED,
+                    _ => color,
+                };
+                draw_field(
+                    gizmos,
+                    &child.field,
+                    &transform.mul_transform(*child.transform()),
+                    color,
+                );
             }
         }
+        // the unmodified field for reference, the actual surface is traced around it
+        Field::Modified(modified) => {
+            draw_field(gizmos, modified.field(), transform, css::GRAY);
+        }
     }
 }
+
+/// collects the contours of every modified field in `field`, in the space of `transform`
+fn trace_field_contours(
+    field: &Field,
+    transform: &GlobalTransform,
+    color: Srgba,
+    contours: &mut FieldContours,
+) {
+    match field {
+        Field::Composite(composite) => {
+            for (i, child) in composite.children.iter().enumerate() {
+                let color = match composite.operation {
+                    CsgOperation::Subtraction if i > 0 => css::ORANGE_RED,
+                    _ => color,
+                };
+                trace_field_contours(
+                    &child.field,
+                    &transform.mul_transform(*child.transform()),
+                    color,
+                    contours,
+                );
+            }
+        }
+        Field::Modified(modified) => {
+            trace_field_contours(modified.field(), transform, css::GRAY, contours);
+            if let Some(bounds) = modified.surface_bounds() {
+                contours.extend(field_contours(field, bounds).map(|points| {
+                    let points = points.into_iter();
+                    (
+                        color,
+                        points
+                            .map(|p| p.map(|p| transform.transform_point(p)))
+                            .collect(),
+                    )
+                }));
+            }
+        }
+        _ => {}
+    }
+}
+
+/// traces rays inwards from the bounding sphere to find where the surface crosses the three planes
+/// through the center of the local `bounds`
+fn field_contours(field: &Field, bounds: Aabb3d) -> impl Iterator<Item = Vec<Option<Vec3>>> {
+    const RESOLUTION: usize = 48;
+    let center = Vec3::from(bounds.center());
+    let radius = bounds.half_size().length() + 0.01;
+    let settings = SuisRaymarchSettings {
+        max_distance: radius * 2.0,
+        ..default()
+    };
+    [(Vec3::X, Vec3::Y), (Vec3::Y, Vec3::Z), (Vec3::Z, Vec3::X)]
+        .map(|(a, b)| {
+            (0..=RESOLUTION)
+                .map(|i| {
+                    let (sin, cos) = (i as f32 / RESOLUTION as f32 * TAU).sin_cos();
+                    let direction = Dir3::new_unchecked(-(a * cos + b * sin));
+                    let ray = Ray3d::new(center - direction * radius, direction);
+                    let hit = field
+                        .raymarch(&GlobalTransform::IDENTITY, ray, &settings)
+                        .hit?;
+                    // inverted fields contain the start of the ray, their surface is where it leaves
+                    let length = if hit.entry > 0.0 { hit.entry } else { hit.exit };
+                    (length <= settings.max_distance).then(|| ray.get_point(length))
+                })
+                .collect()
+        })
+        .into_iter()
+}
@@ -0,0 +1,129 @@
+use bevy::prelude::*;
+
+use crate::{
+    InputMethodDisabled, SuisPreUpdateSets,
+    input_method::InputMethod,
+    input_method_data::{NonSpatialInputData, SpatialInputData},
+    order_helper::update_handler_orders,
+    update_input_method_disabled,
+};
+
+pub struct SuisGazeInputMethodPlugin;
+
+impl Plugin for SuisGazeInputMethodPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<GazeInputMethod>()
+            .register_type::<GazeDwell>();
+        app.add_systems(
+            PreUpdate,
+            update_gaze_rays.in_set(SuisPreUpdateSets::UpdateInputMethods),
+        );
+        app.add_systems(
+            PreUpdate,
+            update_gaze_dwell
+                .after(update_handler_orders)
+                .in_set(SuisPreUpdateSets::OrderHandlers),
+        );
+    }
+}
+
+/// A hands-free input method, a ray along the forward direction of `source` that selects the
+/// handler it rests on for `dwell_time`. It's disabled while `source` doesn't exist.
+#[derive(Clone, Copy, Component, Debug, Reflect)]
+#[reflect(Component, Debug)]
+#[require(InputMethod, GazeDwell)]
+pub struct GazeInputMethod {
+    /// the entity the ray follows, like the XR camera for head gaze or an entity driven by an
+    /// eye tracker
+    #[entities]
+    pub source: Entity,
+    /// in seconds
+    pub dwell_time: f32,
+}
+
+impl GazeInputMethod {
+    pub fn new(source: Entity) -> Self {
+        Self {
+            source,
+            dwell_time: 1.0,
+        }
+    }
+    pub fn with_dwell_time(mut self, dwell_time: f32) -> Self {
+        self.dwell_time = dwell_time;
+        self
+    }
+}
+
+/// How long a [`GazeInputMethod`] has been resting on the same handler, for drawing a fill ring.
+/// `select` is set for a single frame once the progress reaches 1, looking at another handler
+/// starts over.
+#[derive(Clone, Copy, Component, Debug, Default, PartialEq, Reflect)]
+#[reflect(Component, Debug, Default, PartialEq)]
+pub struct GazeDwell {
+    /// the first handler in the handler order, as long as the ray hits or snaps to it
+    #[entities]
+    pub handler: Option<Entity>,
+    /// from 0 to 1
+    pub progress: f32,
+}
+
+fn update_gaze_rays(
+    sources: Query<&GlobalTransform>,
+    mut methods: Query<(
+        Entity,
+        &GazeInputMethod,
+        &mut SpatialInputData,
+        Has<InputMethodDisabled>,
+    )>,
+    mut cmds: Commands,
+) {
+    for (entity, gaze, mut spatial_data, disabled) in &mut methods {
+        let source = sources
+            .get(gaze.source)
+            .inspect_err(|err| warn_once!("invalid gaze source: {err}"));
+        if let Ok(source) = source {
+            *spatial_data =
+                SpatialInputData::Ray(Ray3d::new(source.translation(), source.forward()));
+        }
+        update_input_method_disabled(&mut cmds, entity, source.is_ok(), disabled);
+    }
+}
+
+fn update_gaze_dwell(
+    mut methods: Query<(
+        &mut NonSpatialInputData,
+        &mut GazeDwell,
+        &GazeInputMethod,
+        &InputMethod,
+    )>,
+    time: Res<Time>,
+) {
+    for (mut non_spatial_data, mut dwell, gaze, method) in &mut methods {
+        // the nearest handler doesn't count when the ray doesn't actually point at it
+        let handler = method
+            .get_handler_order()
+            .first()
+            .copied()
+            .filter(|handler| {
+                method
+                    .get_handler_distances()
+                    .iter()
+                    .find(|distance| distance.handler == *handler)
+                    .is_some_and(|distance| {
+                        distance.distance.hit.is_some() || distance.snap.is_some()
+                    })
+            });
+        if dwell.handler != handler {
+            *dwell = GazeDwell {
+                handler,
+                progress: 0.0,
+            };
+        }
+        let was_done = dwell.progress >= 1.0;
+        if dwell.handler.is_some() {
+            let step = time.delta_secs() / gaze.dwell_time.max(f32::EPSILON);
+            dwell.progress = (dwell.progress + step).min(1.0);
+        }
+        non_spatial_data.select = (!was_done && dwell.progress >= 1.0) as u8 as f32;
+    }
+}
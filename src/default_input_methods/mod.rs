@@ -1,5 +1,7 @@
 use bevy::app::{PluginGroup, PluginGroupBuilder};
 
+pub mod gaze;
+pub mod tracked_entity;
 pub mod window_pointer_rays;
 #[cfg(feature = "xr")]
 pub mod xr_controllers;
@@ -10,7 +12,9 @@ pub struct SuisBundledInputMethodPlugins;
 impl PluginGroup for SuisBundledInputMethodPlugins {
     fn build(self) -> PluginGroupBuilder {
         let group = PluginGroupBuilder::start::<Self>()
-            .add(window_pointer_rays::SuisWindowPointerRayPlugin);
+            .add(window_pointer_rays::SuisWindowPointerRayPlugin)
+            .add(gaze::SuisGazeInputMethodPlugin)
+            .add(tracked_entity::SuisTrackedEntityInputMethodPlugin);
         #[cfg(feature = "xr")]
         let group = group
             .add(xr_hands::SuisBundledXrHandsInputMethodPlugin)
@@ -0,0 +1,46 @@
+use bevy::prelude::*;
+
+use crate::{SuisPreUpdateSets, input_method::InputMethod, input_method_data::SpatialInputData};
+
+pub struct SuisTrackedEntityInputMethodPlugin;
+
+impl Plugin for SuisTrackedEntityInputMethodPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            PreUpdate,
+            update_tracked_entity_methods.in_set(SuisPreUpdateSets::UpdateInputMethods),
+        );
+    }
+}
+
+/// Turns this entity into an input method that follows its [`GlobalTransform`], for things like a
+/// held pen, a virtual tool or a tracked puck. The [`NonSpatialInputData`] of the entity is left to
+/// the app, like the button state of the pen.
+///
+/// [`NonSpatialInputData`]: crate::input_method_data::NonSpatialInputData
+#[derive(Clone, Copy, Component, Debug, Default, PartialEq, Eq)]
+#[require(InputMethod, Transform)]
+pub enum TrackedEntityInputMethod {
+    /// a [`SpatialInputData::Tip`] at the origin of the entity
+    #[default]
+    Tip,
+    /// a [`SpatialInputData::Ray`] along the forward direction of the entity
+    Ray,
+}
+
+fn update_tracked_entity_methods(
+    mut methods: Query<(
+        &mut SpatialInputData,
+        &TrackedEntityInputMethod,
+        &GlobalTransform,
+    )>,
+) {
+    for (mut spatial_data, tracked, transform) in &mut methods {
+        *spatial_data = match tracked {
+            TrackedEntityInputMethod::Tip => SpatialInputData::Tip(transform.to_isometry()),
+            TrackedEntityInputMethod::Ray => {
+                SpatialInputData::Ray(Ray3d::new(transform.translation(), transform.forward()))
+            }
+        };
+    }
+}
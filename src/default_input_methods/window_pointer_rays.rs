@@ -1,5 +1,3 @@
-use std::cmp::Ordering;
-
 use bevy::{
     ecs::{component::HookContext, world::DeferredWorld},
     input::mouse::MouseWheel,
@@ -12,7 +10,6 @@ use crate::{
     InputMethodDisabled, SuisPreUpdateSets,
     input_method::InputMethod,
     input_method_data::{NonSpatialInputData, SpatialInputData},
-    order_helper::InputHandlerQueryHelper,
 };
 
 pub struct SuisWindowPointerRayPlugin;
@@ -89,18 +86,10 @@ impl Default for SuisMouseConfig {
 
 // doesn't handle multiple windows correctly
 fn update_mouse_data(
-    mut query: Query<
-        (
-            &mut NonSpatialInputData,
-            &mut InputMethod,
-            &SpatialInputData,
-        ),
-        With<MouseInputMethod>,
-    >,
+    mut query: Query<&mut NonSpatialInputData, With<MouseInputMethod>>,
     mut scroll: EventReader<MouseWheel>,
     buttons: Res<ButtonInput<MouseButton>>,
     config: Res<SuisMouseConfig>,
-    handler_query: InputHandlerQueryHelper,
 ) {
     let mut discrete = Vec2::ZERO;
     let mut continuous = Vec2::ZERO;
@@ -116,7 +105,7 @@ fn update_mouse_data(
             }
         }
     }
-    for (mut data, mut input_method, spatial_data) in query.iter_mut() {
+    for mut data in query.iter_mut() {
         data.select = buttons.pressed(MouseButton::Left) as u8 as f32;
         data.context = buttons.pressed(MouseButton::Middle) as u8 as f32;
         data.secondary = buttons.pressed(MouseButton::Right) as u8 as f32;
@@ -124,14 +113,6 @@ fn update_mouse_data(
         data.scroll = Some(
             (discrete * config.discrete_multiplier) + (continuous * config.continuous_multiplier),
         );
-        let mut handlers =
-            handler_query.query_all_handler_fields(|(handler, field, field_transform)| {
-                (handler, spatial_data.distance(field, field_transform))
-            });
-
-        handlers.sort_by(|(_, d1), (_, d2)| d1.partial_cmp(d2).unwrap_or(Ordering::Equal));
-        let handlers = handlers.into_iter().map(|(e, _)| e).collect();
-        input_method.set_handler_order(handlers);
     }
 }
 
@@ -1,8 +1,6 @@
 pub mod default_bindings;
 pub mod interaction_profiles;
 
-use std::cmp::Ordering;
-
 use default_bindings::{
     SuisXrControllerActions, SuisXrControllerBindingSet, XrControllerInputActions,
 };
@@ -21,7 +19,8 @@ use crate::{
     InputMethodDisabled,
     input_method::InputMethod,
     input_method_data::{NonSpatialInputData, SpatialInputData},
-    order_helper::InputHandlerQueryHelper,
+    parabolic_arc::ParabolicArc,
+    shape_cast::ShapeCast,
     update_input_method_disabled,
 };
 
@@ -42,11 +41,7 @@ impl Plugin for SuisBundledXrControllerInputMethodPlugin {
         app.add_systems(Startup, setup.after(SuisXrControllerBindingSet));
         app.add_systems(
             PreUpdate,
-            (
-                update_method_state,
-                update_method_data,
-                update_handler_order,
-            )
+            (update_method_state, update_method_data)
                 .chain()
                 .after(SchminputSet::SyncInputActions)
                 .after(XrSpaceSyncSet)
@@ -55,21 +50,6 @@ impl Plugin for SuisBundledXrControllerInputMethodPlugin {
     }
 }
 
-fn update_handler_order(
-    mut query: Query<(&mut InputMethod, &SpatialInputData), With<SuisXrControllerInputMethod>>,
-    handler_query: InputHandlerQueryHelper,
-) {
-    for (mut method, spatial_data) in &mut query {
-        let mut handlers =
-            handler_query.query_all_handler_fields(|(handler, field, field_transform)| {
-                (handler, spatial_data.distance(field, field_transform))
-            });
-        handlers.sort_by(|(_, d1), (_, d2)| d1.partial_cmp(d2).unwrap_or(Ordering::Equal));
-        let handlers = handlers.into_iter().map(|(e, _)| e).collect();
-        method.set_handler_order(handlers);
-    }
-}
-
 fn update_method_state(
     query: Query<(Entity, Has<InputMethodDisabled>, &HandSide), With<SuisXrControllerInputMethod>>,
     left_pose: Query<&XrSpaceLocationFlags, (With<SuisXrControllerPoseSource>, With<LeftHand>)>,
@@ -185,12 +165,29 @@ fn update_method_data(
                 pose.translation.into(),
                 pose.rotation * Dir3::NEG_Z,
             )),
+            SpatialInputData::FiniteRay { max_length, .. } => SpatialInputData::FiniteRay {
+                ray: Ray3d::new(pose.translation.into(), pose.rotation * Dir3::NEG_Z),
+                max_length,
+            },
+            // keeps the launch speed, gravity and length
+            SpatialInputData::Arc(arc) => SpatialInputData::Arc(ParabolicArc {
+                origin: pose.translation.into(),
+                velocity: pose.rotation * Vec3::NEG_Z * arc.velocity.length(),
+                ..arc
+            }),
+            SpatialInputData::Cast(cast) => SpatialInputData::Cast(ShapeCast {
+                ray: Ray3d::new(pose.translation.into(), pose.rotation * Dir3::NEG_Z),
+                ..cast
+            }),
         }
     }
 }
 
+/// The input methods of both controllers, they start out as tips. Replace their
+/// [`SpatialInputData`] with a ray, finite ray, arc or cast to switch modes, only the pose gets
+/// updated after that.
 #[derive(Default, Component)]
-struct SuisXrControllerInputMethod;
+pub struct SuisXrControllerInputMethod;
 #[derive(Default, Component)]
 struct SuisXrControllerPoseSource;
 
@@ -1,5 +1,3 @@
-use std::cmp::Ordering;
-
 use bevy::prelude::*;
 use bevy_mod_xr::{
     hands::{
@@ -15,7 +13,6 @@ use crate::{
     hand::{Finger, Hand, Joint, Thumb},
     input_method::InputMethod,
     input_method_data::{NonSpatialInputData, SpatialInputData},
-    order_helper::InputHandlerQueryHelper,
     update_input_method_disabled,
 };
 pub struct SuisBundledXrHandsInputMethodPlugin;
@@ -60,7 +57,6 @@ fn update_active(
 fn update_data(
     mut query: Query<
         (
-            &mut InputMethod,
             &mut SpatialInputData,
             &mut NonSpatialInputData,
             &HandtrackingJoints,
@@ -68,9 +64,8 @@ fn update_data(
         With<SuisBundledXrHandInputMethod>,
     >,
     joint_query: Query<(&GlobalTransform, &XrHandBoneRadius)>,
-    handler_query: InputHandlerQueryHelper,
 ) {
-    for (mut input_method, mut spatial_data, mut non_spatial_data, joints) in &mut query {
+    for (mut spatial_data, mut non_spatial_data, joints) in &mut query {
         let Ok(joint_data) = joint_query.get_many(joints.0) else {
             warn!("unable to get joints!");
             continue;
@@ -89,15 +84,6 @@ fn update_data(
             &GlobalTransform::IDENTITY,
         );
         *spatial_data = SpatialInputData::Hand(hand);
-
-        let mut handlers =
-            handler_query.query_all_handler_fields(|(handler, field, field_transform)| {
-                (handler, spatial_data.distance(field, field_transform))
-            });
-
-        handlers.sort_by(|(_, d1), (_, d2)| d1.partial_cmp(d2).unwrap_or(Ordering::Equal));
-        let handlers = handlers.into_iter().map(|(e, _)| e).collect();
-        input_method.set_handler_order(handlers);
     }
 }
 
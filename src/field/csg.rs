@@ -0,0 +1,234 @@
+use bevy::{
+    math::{
+        Affine3A, Vec3A,
+        bounding::{Aabb3d, BoundingVolume},
+    },
+    prelude::*,
+};
+
+use super::{Field, SignedDistance, finite_difference_normal, min_scale, transform_bounds};
+
+/// A [`Field`] built out of other fields, each placed with its own transform
+#[derive(Debug, Clone, Reflect)]
+// the children contain fields again, which the derived bounds can't handle
+#[reflect(Debug, Clone, no_field_bounds)]
+pub struct CompositeField {
+    pub operation: CsgOperation,
+    pub children: Vec<FieldChild>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Reflect)]
+#[reflect(Debug, Clone, PartialEq)]
+pub enum CsgOperation {
+    Union,
+    Intersection,
+    /// subtracts every other child from the first one
+    Subtraction,
+    /// union with the edges between children rounded off over the blend radius
+    SmoothUnion(f32),
+}
+
+/// A [`Field`] placed relative to the parent [`CompositeField`]
+#[derive(Debug, Clone, Reflect)]
+#[reflect(Debug, Clone)]
+pub struct FieldChild {
+    transform: Transform,
+    pub field: Field,
+    /// computed from `transform` whenever it changes, and again after being loaded from a scene
+    #[reflect(ignore)]
+    cache: ChildTransformCache,
+}
+
+/// The inverse transform of a [`FieldChild`], which is needed for every distance
+#[derive(Debug, Clone, Copy)]
+struct ChildTransformCache {
+    parent_to_child: Affine3A,
+    distance_scale: f32,
+}
+
+impl ChildTransformCache {
+    fn new(transform: &Transform) -> Self {
+        let child_to_parent = transform.compute_affine();
+        Self {
+            parent_to_child: child_to_parent.inverse(),
+            distance_scale: min_scale(child_to_parent.matrix3),
+        }
+    }
+}
+
+impl Default for ChildTransformCache {
+    fn default() -> Self {
+        Self::new(&Transform::IDENTITY)
+    }
+}
+
+impl CompositeField {
+    pub fn new(operation: CsgOperation, children: impl IntoIterator<Item = FieldChild>) -> Self {
+        Self {
+            operation,
+            children: children.into_iter().collect(),
+        }
+    }
+    pub fn union(children: impl IntoIterator<Item = FieldChild>) -> Self {
+        Self::new(CsgOperation::Union, children)
+    }
+    pub fn intersection(children: impl IntoIterator<Item = FieldChild>) -> Self {
+        Self::new(CsgOperation::Intersection, children)
+    }
+    pub fn subtraction(children: impl IntoIterator<Item = FieldChild>) -> Self {
+        Self::new(CsgOperation::Subtraction, children)
+    }
+    pub fn smooth_union(blend_radius: f32, children: impl IntoIterator<Item = FieldChild>) -> Self {
+        Self::new(CsgOperation::SmoothUnion(blend_radius), children)
+    }
+}
+
+impl SignedDistance for CompositeField {
+    /// `f32::INFINITY` without children
+    fn distance(&self, p: Vec3A) -> f32 {
+        let mut distances = self.children.iter().map(|child| child.local_distance(p));
+        let Some(first) = distances.next() else {
+            return f32::INFINITY;
+        };
+        match self.operation {
+            CsgOperation::Union => distances.fold(first, f32::min),
+            CsgOperation::Intersection => distances.fold(first, f32::max),
+            CsgOperation::Subtraction => distances.fold(first, |d, other| d.max(-other)),
+            CsgOperation::SmoothUnion(k) => {
+                distances.fold(first, |d, other| smooth_min(d, other, k))
+            }
+        }
+    }
+    fn normal(&self, p: Vec3A) -> Vec3A {
+        if let CsgOperation::SmoothUnion(_) = self.operation {
+            return finite_difference_normal(|p| self.distance(p), p);
+        }
+        // the normal of whichever child defines the surface at `p`, the children cut out of a
+        // subtraction are facing inwards
+        let sign = |i: usize| match self.operation {
+            CsgOperation::Subtraction if i > 0 => -1.0,
+            _ => 1.0,
+        };
+        let children = self
+            .children
+            .iter()
+            .enumerate()
+            .map(|(i, child)| (i, child, child.local_distance(p) * sign(i)));
+        let selected = match self.operation {
+            CsgOperation::Union => children.min_by(|(_, _, d1), (_, _, d2)| d1.total_cmp(d2)),
+            _ => children.max_by(|(_, _, d1), (_, _, d2)| d1.total_cmp(d2)),
+        };
+        selected
+            .map(|(i, child, _)| child.local_normal(p) * sign(i))
+            .unwrap_or(Vec3A::Y)
+    }
+    fn closest_point(&self, p: Vec3A) -> Vec3A {
+        // the distance is only a bound for intersections, subtractions and blends, so a single
+        // step can end up next to the surface instead of on it
+        const MAX_ITERATIONS: usize = 4;
+        const EPSILON: f32 = 0.00001;
+        let mut point = p;
+        for _ in 0..MAX_ITERATIONS {
+            let distance = self.distance(point);
+            if distance.abs() < EPSILON {
+                break;
+            }
+            point -= self.normal(point) * distance;
+        }
+        point
+    }
+    fn bounds(&self) -> Option<Aabb3d> {
+        let mut bounds = self.children.iter().map(FieldChild::local_bounds);
+        match self.operation {
+            CsgOperation::Union | CsgOperation::SmoothUnion(_) => {
+                // a single unbounded child makes the whole union unbounded
+                let merged = bounds
+                    .collect::<Option<Vec<_>>>()?
+                    .into_iter()
+                    .reduce(|a, b| a.merge(&b))?;
+                let CsgOperation::SmoothUnion(k) = self.operation else {
+                    return Some(merged);
+                };
+                // every blend can bulge out by up to a quarter of the blend radius
+                let bulge = k.max(0.0) / 4.0 * self.children.len().saturating_sub(1) as f32;
+                Some(merged.grow(Vec3A::splat(bulge)))
+            }
+            // unbounded children don't matter, everything is still inside of the bounded ones
+            CsgOperation::Intersection => bounds.flatten().reduce(|a, b| {
+                let min = a.min.max(b.min);
+                Aabb3d {
+                    min,
+                    max: a.max.min(b.max).max(min),
+                }
+            }),
+            CsgOperation::Subtraction => bounds.next()?,
+        }
+    }
+}
+
+impl FieldChild {
+    pub fn new(field: Field, transform: Transform) -> Self {
+        Self {
+            transform,
+            field,
+            cache: ChildTransformCache::new(&transform),
+        }
+    }
+    /// relative to the parent composite
+    pub fn transform(&self) -> &Transform {
+        &self.transform
+    }
+    pub fn set_transform(&mut self, transform: Transform) {
+        self.transform = transform;
+        self.cache = ChildTransformCache::new(&transform);
+    }
+    /// point should be in the local space of the parent composite, the distance is in its units
+    /// too, see [`Field::distance`]
+    pub fn local_distance(&self, p: Vec3A) -> f32 {
+        let distance = self
+            .field
+            .local_distance(self.cache.parent_to_child.transform_point3a(p));
+        distance * self.cache.distance_scale
+    }
+    /// in the local space of the parent composite
+    pub fn local_bounds(&self) -> Option<Aabb3d> {
+        Some(transform_bounds(
+            self.field.local_bounds()?,
+            self.transform.compute_affine(),
+        ))
+    }
+    /// point should be in the local space of the parent composite
+    pub fn local_normal(&self, p: Vec3A) -> Vec3A {
+        let parent_to_child = self.cache.parent_to_child;
+        let normal = self
+            .field
+            .local_normal(parent_to_child.transform_point3a(p));
+        (parent_to_child.matrix3.transpose() * normal).normalize_or(Vec3A::Y)
+    }
+}
+
+impl Field {
+    /// recomputes the transforms cached in every [`FieldChild`] of this field, which are only out
+    /// of date after the field got reflected or loaded from a scene
+    pub(crate) fn update_child_transforms(&mut self) {
+        match self {
+            Field::Composite(composite) => {
+                for child in &mut composite.children {
+                    child.cache = ChildTransformCache::new(&child.transform);
+                    child.field.update_child_transforms();
+                }
+            }
+            Field::Modified(modified) => modified.field_mut().update_child_transforms(),
+            _ => {}
+        }
+    }
+}
+
+/// polynomial smooth min, falls back to [`f32::min`] for a non-positive blend radius
+fn smooth_min(a: f32, b: f32, k: f32) -> f32 {
+    if k <= 0.0 {
+        return a.min(b);
+    }
+    let h = (0.5 + 0.5 * (b - a) / k).clamp(0.0, 1.0);
+    b.lerp(a, h) - k * h * (1.0 - h)
+}
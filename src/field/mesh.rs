@@ -0,0 +1,584 @@
+use std::{fmt, sync::Arc};
+
+use bevy::{
+    asset::{AssetLoader, LoadContext, io::Reader},
+    math::{Vec3A, bounding::Aabb3d},
+    platform::collections::{HashMap, HashSet},
+    prelude::*,
+    render::mesh::{PrimitiveTopology, VertexAttributeValues},
+    tasks::{AsyncComputeTaskPool, Task, block_on, futures_lite::future::poll_once},
+};
+
+use crate::{
+    SuisPreUpdateSets,
+    bvh::{Bvh, BvhNode},
+};
+
+use super::{Field, SignedDistance, primitives::closest_point_on_triangle};
+
+/// Bakes [`MeshField`]s and loads cached `.meshsdf` files. Not part of
+/// [`SuisPlugins`](crate::SuisPlugins) as it needs the [`AssetPlugin`] and the [`Mesh`] assets of
+/// the render plugins, add it yourself to use mesh fields.
+pub struct SuisMeshFieldPlugin;
+
+impl Plugin for SuisMeshFieldPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<MeshSdf>();
+        app.init_asset_loader::<MeshSdfLoader>();
+        app.init_resource::<MeshSdfBaker>();
+        app.add_systems(
+            PreUpdate,
+            (poll_bake_tasks, update_mesh_fields)
+                .chain()
+                .before(SuisPreUpdateSets::PrepareMethodEvents),
+        );
+    }
+}
+
+/// A field shaped like a [`Mesh`], using a signed distance grid baked from it.
+/// The distance is `f32::INFINITY` until the grid is available.
+#[derive(Debug, Clone, Reflect)]
+#[reflect(Debug, Clone)]
+pub struct MeshField {
+    /// the mesh the grid is baked from, unused when `baked` is already set
+    pub mesh: Handle<Mesh>,
+    /// samples along the longest side of the mesh bounds
+    pub resolution: u32,
+    /// the baked grid, set once baking is done so it can be saved with [`MeshSdf::to_bytes`] and
+    /// loaded from a `.meshsdf` file on the next launch instead of baking it again
+    pub baked: Option<Handle<MeshSdf>>,
+    #[reflect(ignore)]
+    sdf: Option<MeshSdf>,
+}
+
+impl MeshField {
+    pub const DEFAULT_RESOLUTION: u32 = 32;
+
+    pub fn new(mesh: Handle<Mesh>) -> Self {
+        Self::with_resolution(mesh, Self::DEFAULT_RESOLUTION)
+    }
+    pub fn with_resolution(mesh: Handle<Mesh>, resolution: u32) -> Self {
+        Self {
+            mesh,
+            resolution,
+            baked: None,
+            sdf: None,
+        }
+    }
+    pub fn from_baked(baked: Handle<MeshSdf>) -> Self {
+        Self {
+            mesh: Handle::default(),
+            resolution: Self::DEFAULT_RESOLUTION,
+            baked: Some(baked),
+            sdf: None,
+        }
+    }
+    /// the grid currently used by this field
+    pub fn sdf(&self) -> Option<&MeshSdf> {
+        self.sdf.as_ref()
+    }
+}
+
+impl SignedDistance for MeshField {
+    fn distance(&self, p: Vec3A) -> f32 {
+        self.sdf
+            .as_ref()
+            .map(|sdf| sdf.distance(p))
+            .unwrap_or(f32::INFINITY)
+    }
+    fn closest_point(&self, p: Vec3A) -> Vec3A {
+        match self.sdf.is_some() {
+            true => p - self.normal(p) * self.distance(p),
+            false => p,
+        }
+    }
+    /// the bounds of the baked grid
+    fn bounds(&self) -> Option<Aabb3d> {
+        self.sdf.as_ref().map(|sdf| {
+            let (min, max) = sdf.bounds();
+            Aabb3d { min, max }
+        })
+    }
+}
+
+/// Signed distances sampled on a regular grid in the local space of a mesh
+#[derive(Asset, TypePath, Debug, Clone)]
+pub struct MeshSdf {
+    min: Vec3A,
+    max: Vec3A,
+    resolution: UVec3,
+    distances: Arc<[f32]>,
+}
+
+impl MeshSdf {
+    const MAGIC: &[u8; 4] = b"SSDF";
+    const HEADER_LEN: usize = 4 + 3 * 4 + 6 * 4;
+
+    /// bakes the triangles of the mesh, `None` if it isn't a triangle list with positions
+    pub fn bake(mesh: &Mesh, resolution: u32) -> Option<MeshSdf> {
+        let triangles = mesh_triangles(mesh)?;
+        Some(Self::bake_triangles(&triangles, resolution))
+    }
+
+    /// samples the distance of every grid point to the closest triangle, using the winding number
+    /// for the sign so meshes with small holes still work
+    pub fn bake_triangles(triangles: &[[Vec3A; 3]], resolution: u32) -> MeshSdf {
+        let (min, max) = triangles
+            .iter()
+            .flatten()
+            .fold((Vec3A::INFINITY, Vec3A::NEG_INFINITY), |(min, max), v| {
+                (min.min(*v), max.max(*v))
+            });
+        if !min.is_finite() {
+            return MeshSdf {
+                min: Vec3A::ZERO,
+                max: Vec3A::ZERO,
+                resolution: UVec3::ONE,
+                distances: Arc::new([f32::INFINITY]),
+            };
+        }
+        let resolution = resolution.max(2);
+        // pad the bounds so the outside of the surface is inside of the grid
+        let padding = (max - min).max_element() / (resolution - 1) as f32 * 2.0;
+        let (min, max) = (min - padding, max + padding);
+        let spacing = (max - min).max_element() / (resolution - 1) as f32;
+        let resolution = (((max - min) / spacing).ceil().as_uvec3() + 1).max(UVec3::splat(2));
+        let max = min + (resolution - 1).as_vec3a() * spacing;
+
+        let bvh = TriangleBvh::new(triangles);
+        let len = resolution.x as usize * resolution.y as usize * resolution.z as usize;
+        let mut distances = Vec::with_capacity(len);
+        for z in 0..resolution.z {
+            for y in 0..resolution.y {
+                for x in 0..resolution.x {
+                    let p = min + uvec3(x, y, z).as_vec3a() * spacing;
+                    let inside = bvh.solid_angle(p) / (4.0 * std::f32::consts::PI) > 0.5;
+                    let distance = bvh.distance_squared(p).sqrt();
+                    distances.push(if inside { -distance } else { distance });
+                }
+            }
+        }
+        MeshSdf {
+            min,
+            max,
+            resolution,
+            distances: distances.into(),
+        }
+    }
+
+    /// the local space bounds covered by the grid
+    pub fn bounds(&self) -> (Vec3A, Vec3A) {
+        (self.min, self.max)
+    }
+
+    /// trilinearly sampled inside of the grid. Outside of it the sample at the closest point on the
+    /// grid bounds only gives a lower bound, the distance can't shrink faster than moving away from
+    /// that point, and the surface is never outside of the bounds either.
+    pub fn distance(&self, p: Vec3A) -> f32 {
+        let clamped = p.clamp(self.min, self.max);
+        if clamped == p {
+            return self.sample(p);
+        }
+        let box_distance = p.distance(clamped);
+        box_distance.max(self.sample(clamped) - box_distance)
+    }
+
+    fn sample(&self, p: Vec3A) -> f32 {
+        let last = (self.resolution - 1).as_vec3a();
+        let grid_pos = ((p - self.min) / (self.max - self.min) * last).clamp(Vec3A::ZERO, last);
+        // a grid with a single sample along an axis divides by zero
+        let grid_pos = Vec3A::select(grid_pos.is_nan_mask(), Vec3A::ZERO, grid_pos);
+        let base = grid_pos
+            .floor()
+            .as_uvec3()
+            .min(self.resolution.saturating_sub(UVec3::splat(2)));
+        let t = grid_pos - base.as_vec3a();
+        let at = |x: u32, y: u32, z: u32| {
+            let pos = (base + uvec3(x, y, z)).min(self.resolution - 1);
+            self.distances[(pos.x
+                + pos.y * self.resolution.x
+                + pos.z * self.resolution.x * self.resolution.y)
+                as usize]
+        };
+        let x00 = at(0, 0, 0).lerp(at(1, 0, 0), t.x);
+        let x10 = at(0, 1, 0).lerp(at(1, 1, 0), t.x);
+        let x01 = at(0, 0, 1).lerp(at(1, 0, 1), t.x);
+        let x11 = at(0, 1, 1).lerp(at(1, 1, 1), t.x);
+        x00.lerp(x10, t.y).lerp(x01.lerp(x11, t.y), t.z)
+    }
+
+    /// the format read by the `.meshsdf` asset loader
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(Self::HEADER_LEN + self.distances.len() * 4);
+        bytes.extend_from_slice(Self::MAGIC);
+        for v in self.resolution.to_array() {
+            bytes.extend_from_slice(&v.to_le_bytes());
+        }
+        for v in self.min.to_array().into_iter().chain(self.max.to_array()) {
+            bytes.extend_from_slice(&v.to_le_bytes());
+        }
+        for v in self.distances.iter() {
+            bytes.extend_from_slice(&v.to_le_bytes());
+        }
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<MeshSdf, MeshSdfLoadError> {
+        let (header, data) = bytes
+            .split_at_checked(Self::HEADER_LEN)
+            .ok_or(MeshSdfLoadError::Invalid)?;
+        if &header[..4] != Self::MAGIC {
+            return Err(MeshSdfLoadError::Invalid);
+        }
+        let words = |bytes: &[u8]| {
+            bytes
+                .chunks_exact(4)
+                .map(|b| [b[0], b[1], b[2], b[3]])
+                .collect::<Vec<_>>()
+        };
+        let header = words(&header[4..]);
+        let resolution = UVec3::from_array([0, 1, 2].map(|i| u32::from_le_bytes(header[i])));
+        let min = Vec3A::from_array([3, 4, 5].map(|i| f32::from_le_bytes(header[i])));
+        let max = Vec3A::from_array([6, 7, 8].map(|i| f32::from_le_bytes(header[i])));
+        let distances = words(data)
+            .into_iter()
+            .map(f32::from_le_bytes)
+            .collect::<Arc<[f32]>>();
+        // the resolution comes from the file, so the product can overflow
+        let len = resolution
+            .to_array()
+            .into_iter()
+            .try_fold(1usize, |len, v| len.checked_mul(v as usize));
+        if resolution.cmpeq(UVec3::ZERO).any() || len != Some(distances.len()) {
+            return Err(MeshSdfLoadError::Invalid);
+        }
+        Ok(MeshSdf {
+            min,
+            max,
+            resolution,
+            distances,
+        })
+    }
+}
+
+fn mesh_triangles(mesh: &Mesh) -> Option<Vec<[Vec3A; 3]>> {
+    if mesh.primitive_topology() != PrimitiveTopology::TriangleList {
+        return None;
+    }
+    let Some(VertexAttributeValues::Float32x3(positions)) =
+        mesh.attribute(Mesh::ATTRIBUTE_POSITION)
+    else {
+        return None;
+    };
+    let indices = match mesh.indices() {
+        Some(indices) => indices.iter().collect::<Vec<_>>(),
+        None => (0..positions.len()).collect(),
+    };
+    indices
+        .chunks_exact(3)
+        .map(|tri| {
+            let [a, b, c] = [tri[0], tri[1], tri[2]].map(|i| positions.get(i).copied());
+            Some([Vec3A::from(a?), Vec3A::from(b?), Vec3A::from(c?)])
+        })
+        .collect()
+}
+
+/// A bounding volume hierarchy over the triangles of a mesh for baking, with one triangle per
+/// leaf like the [`FieldBroadphase`](crate::broadphase::FieldBroadphase)
+struct TriangleBvh {
+    bvh: Bvh<[Vec3A; 3]>,
+    /// one for every node of `bvh`
+    dipoles: Vec<Dipole>,
+}
+
+/// The triangles of a branch seen from far away, for the fast winding numbers of "Fast Winding
+/// Numbers for Soups and Clouds" by Barill et al.
+#[derive(Clone, Copy)]
+struct Dipole {
+    /// the sum of the triangle normals scaled by their area
+    area_normal: Vec3A,
+    area: f32,
+    /// the area weighted center of the triangles
+    center: Vec3A,
+    /// the distance from the center to the furthest vertex
+    radius: f32,
+}
+
+impl Dipole {
+    fn of_triangle([a, b, c]: [Vec3A; 3]) -> Self {
+        let area_normal = (b - a).cross(c - a) / 2.0;
+        let center = (a + b + c) / 3.0;
+        Self {
+            area_normal,
+            area: area_normal.length(),
+            center,
+            radius: [a, b, c]
+                .map(|v| v.distance(center))
+                .into_iter()
+                .fold(0.0, f32::max),
+        }
+    }
+    fn merge(&self, other: &Self) -> Self {
+        let area = self.area + other.area;
+        let center = match area > 0.0 {
+            true => (self.center * self.area + other.center * other.area) / area,
+            false => (self.center + other.center) / 2.0,
+        };
+        Self {
+            area_normal: self.area_normal + other.area_normal,
+            area,
+            center,
+            radius: (self.center.distance(center) + self.radius)
+                .max(other.center.distance(center) + other.radius),
+        }
+    }
+}
+
+impl TriangleBvh {
+    /// branches further away than this many times their radius use their dipole for the winding
+    /// number, which is accurate enough to get the sign right
+    const FAR_FIELD: f32 = 2.0;
+
+    fn new(triangles: &[[Vec3A; 3]]) -> Self {
+        let bvh = Bvh::build(
+            triangles
+                .iter()
+                .map(|triangle| {
+                    let bounds = Aabb3d {
+                        min: triangle[0].min(triangle[1]).min(triangle[2]),
+                        max: triangle[0].max(triangle[1]).max(triangle[2]),
+                    };
+                    (*triangle, bounds)
+                })
+                .collect(),
+        );
+        // children come after their parent, so going backwards merges them first
+        let nodes = bvh.nodes();
+        let mut dipoles = vec![None; nodes.len()];
+        for (index, node) in nodes.iter().enumerate().rev() {
+            dipoles[index] = Some(match node {
+                BvhNode::Leaf { item, .. } => Dipole::of_triangle(*item),
+                BvhNode::Branch {
+                    children: [left, right],
+                    ..
+                } => Dipole::merge(&dipoles[*left].unwrap(), &dipoles[*right].unwrap()),
+            });
+        }
+        let dipoles = dipoles.into_iter().flatten().collect();
+        Self { bvh, dipoles }
+    }
+    /// the squared distance to the closest triangle
+    fn distance_squared(&self, p: Vec3A) -> f32 {
+        let nodes = self.bvh.nodes();
+        let mut closest = f32::INFINITY;
+        let mut stack = Vec::new();
+        if !nodes.is_empty() {
+            stack.push(0);
+        }
+        while let Some(index) = stack.pop() {
+            let node = &nodes[index];
+            if node.bounds().closest_point(p).distance_squared(p) >= closest {
+                continue;
+            }
+            match node {
+                BvhNode::Leaf {
+                    item: [a, b, c], ..
+                } => {
+                    closest =
+                        closest.min(p.distance_squared(closest_point_on_triangle(p, *a, *b, *c)));
+                }
+                BvhNode::Branch { children, .. } => {
+                    // the closer child gets popped first, which culls more of the other one
+                    let distance = |child: usize| {
+                        let bounds = nodes[child].bounds();
+                        bounds.closest_point(p).distance_squared(p)
+                    };
+                    let [near, far] = *children;
+                    match distance(near) <= distance(far) {
+                        true => stack.extend([far, near]),
+                        false => stack.extend([near, far]),
+                    }
+                }
+            }
+        }
+        closest
+    }
+    /// the solid angle of all triangles seen from `p`, exact close to `p` and approximated for
+    /// branches far away from it
+    fn solid_angle(&self, p: Vec3A) -> f32 {
+        let nodes = self.bvh.nodes();
+        let mut solid_angle = 0.0;
+        let mut stack = Vec::new();
+        if !nodes.is_empty() {
+            stack.push(0);
+        }
+        while let Some(index) = stack.pop() {
+            match &nodes[index] {
+                BvhNode::Leaf {
+                    item: [a, b, c], ..
+                } => solid_angle += triangle_solid_angle(p, *a, *b, *c),
+                BvhNode::Branch { children, .. } => {
+                    let dipole = &self.dipoles[index];
+                    let to_center = dipole.center - p;
+                    let distance = to_center.length();
+                    if distance > dipole.radius * Self::FAR_FIELD {
+                        solid_angle += dipole.area_normal.dot(to_center) / distance.powi(3);
+                    } else {
+                        stack.extend(children);
+                    }
+                }
+            }
+        }
+        solid_angle
+    }
+}
+
+/// from "The Solid Angle of a Plane Triangle" by Van Oosterom and Strackee
+fn triangle_solid_angle(p: Vec3A, a: Vec3A, b: Vec3A, c: Vec3A) -> f32 {
+    let (a, b, c) = (a - p, b - p, c - p);
+    let (la, lb, lc) = (a.length(), b.length(), c.length());
+    let numerator = a.dot(b.cross(c));
+    let denominator = la * lb * lc + a.dot(b) * lc + a.dot(c) * lb + b.dot(c) * la;
+    2.0 * numerator.atan2(denominator)
+}
+
+#[derive(Default)]
+pub struct MeshSdfLoader;
+
+impl AssetLoader for MeshSdfLoader {
+    type Asset = MeshSdf;
+    type Settings = ();
+    type Error = MeshSdfLoadError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<MeshSdf, MeshSdfLoadError> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        MeshSdf::from_bytes(&bytes)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["meshsdf"]
+    }
+}
+
+#[derive(Debug)]
+pub enum MeshSdfLoadError {
+    Io(std::io::Error),
+    Invalid,
+}
+
+impl fmt::Display for MeshSdfLoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MeshSdfLoadError::Io(err) => write!(f, "unable to read mesh sdf: {err}"),
+            MeshSdfLoadError::Invalid => write!(f, "invalid mesh sdf data"),
+        }
+    }
+}
+
+impl std::error::Error for MeshSdfLoadError {}
+
+impl From<std::io::Error> for MeshSdfLoadError {
+    fn from(err: std::io::Error) -> Self {
+        MeshSdfLoadError::Io(err)
+    }
+}
+
+/// Shares baked grids between fields using the same mesh and resolution
+#[derive(Resource, Default)]
+struct MeshSdfBaker {
+    baked: HashMap<(AssetId<Mesh>, u32), Handle<MeshSdf>>,
+    tasks: HashMap<(AssetId<Mesh>, u32), Task<MeshSdf>>,
+    unsupported: HashSet<AssetId<Mesh>>,
+}
+
+fn poll_bake_tasks(mut baker: ResMut<MeshSdfBaker>, mut sdfs: ResMut<Assets<MeshSdf>>) {
+    let mut finished = Vec::new();
+    for (key, task) in baker.tasks.iter_mut() {
+        if let Some(result) = block_on(poll_once(task)) {
+            finished.push((*key, result));
+        }
+    }
+    for (key, sdf) in finished {
+        baker.tasks.remove(&key);
+        baker.baked.insert(key, sdfs.add(sdf));
+    }
+}
+
+/// fields waiting for their grid only need another look once they change or once a mesh or grid
+/// gets loaded or baked
+fn update_mesh_fields(
+    mut fields: ParamSet<(Query<&mut Field>, Query<&mut Field, Changed<Field>>)>,
+    mut mesh_events: EventReader<AssetEvent<Mesh>>,
+    mut sdf_events: EventReader<AssetEvent<MeshSdf>>,
+    mut baker: ResMut<MeshSdfBaker>,
+    sdfs: Res<Assets<MeshSdf>>,
+    meshes: Res<Assets<Mesh>>,
+) {
+    let assets_changed = mesh_events.read().count() + sdf_events.read().count() > 0;
+    let mut update = |field: Mut<Field>| update_mesh_field(field, &mut baker, &sdfs, &meshes);
+    if assets_changed {
+        fields.p0().iter_mut().for_each(&mut update);
+    } else {
+        fields.p1().iter_mut().for_each(&mut update);
+    }
+}
+
+fn update_mesh_field(
+    mut field: Mut<Field>,
+    baker: &mut MeshSdfBaker,
+    sdfs: &Assets<MeshSdf>,
+    meshes: &Assets<Mesh>,
+) {
+    let mut changed = false;
+    for_each_mesh_field(field.bypass_change_detection(), &mut |mesh_field| {
+        if mesh_field.sdf.is_some() {
+            return;
+        }
+        let key = (mesh_field.mesh.id(), mesh_field.resolution);
+        if mesh_field.baked.is_none() {
+            mesh_field.baked = baker.baked.get(&key).cloned();
+        }
+        if let Some(baked) = &mesh_field.baked {
+            mesh_field.sdf = sdfs.get(baked).cloned();
+            changed |= mesh_field.sdf.is_some();
+            return;
+        }
+        if baker.tasks.contains_key(&key) || baker.unsupported.contains(&key.0) {
+            return;
+        }
+        let Some(mesh) = meshes.get(&mesh_field.mesh) else {
+            return;
+        };
+        let Some(triangles) = mesh_triangles(mesh) else {
+            error!("unable to bake MeshField, the mesh has to be a triangle list with positions");
+            baker.unsupported.insert(key.0);
+            return;
+        };
+        let resolution = mesh_field.resolution;
+        let task = AsyncComputeTaskPool::get()
+            .spawn(async move { MeshSdf::bake_triangles(&triangles, resolution) });
+        baker.tasks.insert(key, task);
+    });
+    if changed {
+        field.set_changed();
+    }
+}
+
+fn for_each_mesh_field(field: &mut Field, f: &mut impl FnMut(&mut MeshField)) {
+    match field {
+        Field::Mesh(mesh_field) => f(mesh_field),
+        Field::Composite(composite) => {
+            for child in composite.children.iter_mut() {
+                for_each_mesh_field(&mut child.field, f);
+            }
+        }
+        Field::Modified(modified) => for_each_mesh_field(modified.field_mut(), f),
+        _ => {}
+    }
+}
@@ -0,0 +1,404 @@
+mod csg;
+mod mesh;
+mod modifiers;
+mod primitives;
+mod raycast;
+
+use std::{f32::consts::PI, fmt::Debug, sync::Arc};
+
+use bevy::{
+    math::{
+        Affine3A, Mat3A, Vec3A,
+        bounding::{Aabb3d, BoundingVolume},
+        vec3a,
+    },
+    prelude::*,
+};
+pub use csg::{CompositeField, CsgOperation, FieldChild};
+pub use mesh::{MeshField, MeshSdf, MeshSdfLoadError, MeshSdfLoader, SuisMeshFieldPlugin};
+pub use modifiers::{FieldModifier, ModifiedField};
+pub use primitives::{CurvedPanel, Panel, RoundedCuboid};
+pub use raycast::RayIntersection;
+pub(crate) use raycast::golden_section_min;
+
+use crate::raymarching::{RayMarchResult, SuisRaymarchSettings, raymarch_field};
+
+/// A signed distance function in its own local space, implement this for shapes that aren't built
+/// in and use them with [`Field::custom`]
+pub trait SignedDistance: Debug + Send + Sync + 'static {
+    /// negative inside of the shape, should never overestimate the distance to the surface
+    fn distance(&self, p: Vec3A) -> f32;
+    /// the unit length outward direction at `p`, falls back to `Vec3A::Y` where it's undefined.
+    /// Uses finite differences of [`SignedDistance::distance`] unless overridden.
+    fn normal(&self, p: Vec3A) -> Vec3A {
+        finite_difference_normal(|p| self.distance(p), p)
+    }
+    fn closest_point(&self, p: Vec3A) -> Vec3A {
+        p - self.normal(p) * self.distance(p)
+    }
+    /// the local space bounds of the surface, `None` when unknown or unbounded
+    fn bounds(&self) -> Option<Aabb3d> {
+        None
+    }
+    /// the exact intersection with a ray in local space, the ray gets sphere traced using
+    /// [`SignedDistance::distance`] when this returns `None`, which is the default.
+    /// [`RayIntersection::convex`] works for any bounded convex shape.
+    fn ray_intersection(&self, _ray: Ray3d) -> Option<RayIntersection> {
+        None
+    }
+}
+
+/// Where a point is on a field with a 2D surface, like a [`Panel`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SurfaceCoordinates {
+    /// from `0.0` to `1.0` across the surface, starting at the top left like texture coordinates
+    pub uv: Vec2,
+    /// in local units relative to the center, `+Y` is up
+    pub local: Vec2,
+}
+
+/// how much the transform stretches the direction it stretches the least, which is the scale for
+/// uniform scale
+pub(crate) fn min_scale(matrix: Mat3A) -> f32 {
+    // the square root of the smallest eigenvalue of MᵀM, using the closed form for symmetric 3x3
+    // matrices. Without shear MᵀM is just the squared scale on the diagonal.
+    let m = matrix.transpose() * matrix;
+    let diagonal = vec3a(m.x_axis.x, m.y_axis.y, m.z_axis.z);
+    let off_diagonal = m.y_axis.x.powi(2) + m.z_axis.x.powi(2) + m.z_axis.y.powi(2);
+    if off_diagonal == 0.0 {
+        return diagonal.min_element().max(0.0).sqrt();
+    }
+    let mean = diagonal.element_sum() / 3.0;
+    let spread = (((diagonal - mean).length_squared() + 2.0 * off_diagonal) / 6.0).sqrt();
+    let b = (m - Mat3A::from_diagonal(Vec3::splat(mean))) * spread.recip();
+    let angle = (b.determinant() / 2.0).clamp(-1.0, 1.0).acos() / 3.0;
+    let smallest = mean + 2.0 * spread * (angle + 2.0 * PI / 3.0).cos();
+    smallest.max(0.0).sqrt()
+}
+
+/// used for fields without an analytic normal, falls back to `Vec3A::Y` where it's undefined
+fn finite_difference_normal(distance: impl Fn(Vec3A) -> f32, p: Vec3A) -> Vec3A {
+    // tetrahedron technique, only needs 4 distance evaluations
+    const H: f32 = 0.0001;
+    const K1: Vec3A = Vec3A::new(1.0, -1.0, -1.0);
+    const K2: Vec3A = Vec3A::new(-1.0, -1.0, 1.0);
+    const K3: Vec3A = Vec3A::new(-1.0, 1.0, -1.0);
+    const K4: Vec3A = Vec3A::new(1.0, 1.0, 1.0);
+    (K1 * distance(p + K1 * H)
+        + K2 * distance(p + K2 * H)
+        + K3 * distance(p + K3 * H)
+        + K4 * distance(p + K4 * H))
+    .normalize_or(Vec3A::Y)
+}
+
+/// Where a [`Field`] is in the world, implemented for [`FieldCache`] which keeps the inverse
+/// around and for [`GlobalTransform`] which gets inverted on every use
+pub trait FieldTransform {
+    fn local_to_world(&self) -> Affine3A;
+    fn world_to_local(&self) -> Affine3A;
+    /// how many world units a local distance is, see [`Field::distance`]
+    fn distance_scale(&self) -> f32;
+}
+
+impl FieldTransform for GlobalTransform {
+    fn local_to_world(&self) -> Affine3A {
+        self.affine()
+    }
+    fn world_to_local(&self) -> Affine3A {
+        self.affine().inverse()
+    }
+    fn distance_scale(&self) -> f32 {
+        min_scale(self.affine().matrix3)
+    }
+}
+
+/// The transforms and bounds of a [`Field`] that are expensive to compute, updated after
+/// transform propagation in [`PreUpdate`] and [`PostUpdate`]
+#[derive(Component, Debug, Clone, Copy)]
+#[require(Transform)]
+pub struct FieldCache {
+    local_to_world: Affine3A,
+    world_to_local: Affine3A,
+    distance_scale: f32,
+    world_bounds: Option<Aabb3d>,
+}
+
+impl FieldCache {
+    pub fn new(field: &Field, field_transform: &GlobalTransform) -> Self {
+        let local_to_world = field_transform.affine();
+        Self {
+            local_to_world,
+            world_to_local: local_to_world.inverse(),
+            distance_scale: min_scale(local_to_world.matrix3),
+            world_bounds: field.world_bounds(field_transform),
+        }
+    }
+    /// see [`Field::world_bounds`]
+    pub fn world_bounds(&self) -> Option<Aabb3d> {
+        self.world_bounds
+    }
+}
+
+impl Default for FieldCache {
+    fn default() -> Self {
+        Self {
+            local_to_world: Affine3A::IDENTITY,
+            world_to_local: Affine3A::IDENTITY,
+            distance_scale: 1.0,
+            world_bounds: None,
+        }
+    }
+}
+
+impl FieldTransform for FieldCache {
+    fn local_to_world(&self) -> Affine3A {
+        self.local_to_world
+    }
+    fn world_to_local(&self) -> Affine3A {
+        self.world_to_local
+    }
+    fn distance_scale(&self) -> f32 {
+        self.distance_scale
+    }
+}
+
+pub(crate) fn update_field_caches(
+    mut query: Query<
+        (Mut<Field>, &GlobalTransform, &mut FieldCache),
+        Or<(Changed<GlobalTransform>, Changed<Field>, Added<FieldCache>)>,
+    >,
+) {
+    for (mut field, transform, mut cache) in &mut query {
+        if field.is_changed() {
+            // doesn't count as a change, otherwise the field would be updated again every frame
+            field.bypass_change_detection().update_child_transforms();
+        }
+        *cache = FieldCache::new(&field, transform);
+    }
+}
+
+/// the bounds around the transformed corners of `bounds`
+pub(crate) fn transform_bounds(bounds: Aabb3d, transform: Affine3A) -> Aabb3d {
+    let matrix = transform.matrix3;
+    let abs = Mat3A::from_cols(
+        matrix.x_axis.abs(),
+        matrix.y_axis.abs(),
+        matrix.z_axis.abs(),
+    );
+    Aabb3d::new(
+        transform.transform_point3a(bounds.center()),
+        abs * bounds.half_size(),
+    )
+}
+
+#[derive(Component, Debug, Clone, Reflect)]
+#[reflect(Component, Debug, Clone)]
+#[require(FieldCache)]
+pub enum Field {
+    Sphere(f32),
+    Cuboid(Cuboid),
+    Torus(Torus),
+    Cylinder(Cylinder),
+    RoundedCuboid(RoundedCuboid),
+    Capsule(Capsule3d),
+    Cone(Cone),
+    ConicalFrustum(ConicalFrustum),
+    /// a flat rectangle without thickness
+    Plane(Plane3d),
+    /// a flat, optionally rounded rectangle with [`SurfaceCoordinates`], for world-space UI
+    Panel(Panel),
+    /// a section of a cylindrical shell with [`SurfaceCoordinates`] along the arc, for curved
+    /// world-space UI
+    CurvedPanel(CurvedPanel),
+    /// everything behind the plane is inside of the field
+    InfinitePlane(InfinitePlane3d),
+    /// without thickness
+    Triangle(Triangle3d),
+    Tetrahedron(Tetrahedron),
+    /// a line segment without thickness, use [`Field::Capsule`] for a rounded one
+    Segment(Segment3d),
+    /// shaped like a mesh, needs the [`SuisMeshFieldPlugin`] to bake it
+    Mesh(MeshField),
+    /// a user defined shape, see [`SignedDistance`]. It can't be reflected, so a scene round trip
+    /// turns it into an empty field without a surface anywhere, which never gets hit or captures
+    /// anything. Insert it again after loading the scene.
+    Custom(#[reflect(ignore, default = "empty_custom_field")] Arc<dyn SignedDistance>),
+    /// a field combined from multiple child fields, see [`CompositeField`]
+    Composite(CompositeField),
+    /// another field with its surface changed, like rounded or hollowed out, see [`FieldModifier`]
+    Modified(ModifiedField),
+}
+
+/// what a [`Field::Custom`] gets deserialized as, without a surface anywhere
+#[derive(Debug)]
+struct EmptyField;
+
+impl SignedDistance for EmptyField {
+    fn distance(&self, _p: Vec3A) -> f32 {
+        f32::INFINITY
+    }
+}
+
+fn empty_custom_field() -> Arc<dyn SignedDistance> {
+    warn!("Field::Custom can't be reflected, it's replaced by an empty field");
+    Arc::new(EmptyField)
+}
+
+impl Field {
+    pub fn custom(sdf: impl SignedDistance) -> Field {
+        Field::Custom(Arc::new(sdf))
+    }
+    /// places this field at `transform` relative to the parent [`CompositeField`]
+    pub fn with_transform(self, transform: Transform) -> FieldChild {
+        FieldChild::new(self, transform)
+    }
+    /// point should be in world-space, returns a world-space point on the surface. It's the
+    /// closest one in local space, which is only the closest in world-space for uniform scale
+    pub fn closest_point(
+        &self,
+        field_transform: &impl FieldTransform,
+        point: impl Into<Vec3A>,
+    ) -> Vec3A {
+        let p = field_transform
+            .world_to_local()
+            .transform_point3a(point.into());
+        field_transform
+            .local_to_world()
+            .transform_point3a(self.local_closest_point(p))
+    }
+    /// point should be in world-space, falls back to the local `Y` axis where the normal is
+    /// undefined, like the center of a sphere
+    pub fn normal(&self, field_transform: &impl FieldTransform, point: impl Into<Vec3A>) -> Dir3A {
+        let world_to_local = field_transform.world_to_local();
+        let local_normal = self.local_normal(world_to_local.transform_point3a(point.into()));
+        // normals are transformed by the inverse transpose to stay correct under non-uniform scale
+        let normal = world_to_local.matrix3.transpose() * local_normal;
+        Dir3A::new(normal).unwrap_or(Dir3A::Y)
+    }
+    /// point should be in world-space, the distance is in world units. It's exact for uniform
+    /// scale, with non-uniform scale it's scaled by the smallest axis scale so it's never more than
+    /// the actual distance.
+    pub fn distance(&self, field_transform: &impl FieldTransform, point: impl Into<Vec3A>) -> f32 {
+        let p = field_transform
+            .world_to_local()
+            .transform_point3a(point.into());
+        self.local_distance(p) * field_transform.distance_scale()
+    }
+    /// point should be in the local space of the field
+    pub fn local_distance(&self, p: Vec3A) -> f32 {
+        self.with_sdf(|sdf| sdf.distance(p))
+    }
+    /// point should be in the local space of the field, see [`Field::normal`]
+    pub fn local_normal(&self, p: Vec3A) -> Vec3A {
+        self.with_sdf(|sdf| sdf.normal(p))
+    }
+    /// point should be in the local space of the field
+    pub fn local_closest_point(&self, p: Vec3A) -> Vec3A {
+        self.with_sdf(|sdf| sdf.closest_point(p))
+    }
+    /// the local space bounds, `None` when unknown or unbounded
+    pub fn local_bounds(&self) -> Option<Aabb3d> {
+        self.with_sdf(|sdf| sdf.bounds())
+    }
+    /// the world-space bounds, `None` when unknown or unbounded
+    pub fn world_bounds(&self, field_transform: &impl FieldTransform) -> Option<Aabb3d> {
+        Some(transform_bounds(
+            self.local_bounds()?,
+            field_transform.local_to_world(),
+        ))
+    }
+    fn with_sdf<T>(&self, f: impl FnOnce(&dyn SignedDistance) -> T) -> T {
+        match self {
+            Field::Sphere(radius) => f(&Sphere::new(*radius)),
+            Field::Cuboid(cuboid) => f(cuboid),
+            Field::Torus(torus) => f(torus),
+            Field::Cylinder(cylinder) => f(cylinder),
+            Field::RoundedCuboid(rounded_cuboid) => f(rounded_cuboid),
+            Field::Capsule(capsule) => f(capsule),
+            Field::Cone(cone) => f(cone),
+            Field::ConicalFrustum(frustum) => f(frustum),
+            Field::Plane(plane) => f(plane),
+            Field::Panel(panel) => f(panel),
+            Field::CurvedPanel(panel) => f(panel),
+            Field::InfinitePlane(plane) => f(plane),
+            Field::Triangle(triangle) => f(triangle),
+            Field::Tetrahedron(tetrahedron) => f(tetrahedron),
+            Field::Segment(segment) => f(segment),
+            Field::Mesh(mesh) => f(mesh),
+            Field::Custom(custom) => f(custom.as_ref()),
+            Field::Composite(composite) => f(composite),
+            Field::Modified(modified) => f(modified),
+        }
+    }
+    /// whether [`Field::surface_coordinates`] returns anything for this field
+    pub fn has_surface_coordinates(&self) -> bool {
+        matches!(self, Field::Panel(_) | Field::CurvedPanel(_))
+    }
+    /// point should be in world-space, the coordinates of the closest point on the surface,
+    /// `None` for fields without them
+    pub fn surface_coordinates(
+        &self,
+        field_transform: &impl FieldTransform,
+        point: impl Into<Vec3A>,
+    ) -> Option<SurfaceCoordinates> {
+        let p = field_transform
+            .world_to_local()
+            .transform_point3a(point.into());
+        match self {
+            Field::Panel(panel) => Some(panel.surface_coordinates(p)),
+            Field::CurvedPanel(panel) => Some(panel.surface_coordinates(p)),
+            _ => None,
+        }
+    }
+    /// the ray should be in the local space of the field, `None` when the field has no exact
+    /// intersection and has to be sphere traced
+    pub fn local_ray_intersection(&self, ray: Ray3d) -> Option<RayIntersection> {
+        self.with_sdf(|sdf| sdf.ray_intersection(ray))
+    }
+    pub fn raymarch(
+        &self,
+        field_transform: &impl FieldTransform,
+        ray: Ray3d,
+        settings: &SuisRaymarchSettings,
+    ) -> RayMarchResult {
+        raymarch_field(ray, self, field_transform, settings)
+    }
+}
+
+impl From<Sphere> for Field {
+    fn from(sphere: Sphere) -> Self {
+        Field::Sphere(sphere.radius)
+    }
+}
+
+macro_rules! impl_field_from_primitive {
+    ($($primitive:ty => $variant:ident),* $(,)?) => {
+        $(
+            impl From<$primitive> for Field {
+                fn from(primitive: $primitive) -> Self {
+                    Field::$variant(primitive)
+                }
+            }
+        )*
+    };
+}
+
+impl_field_from_primitive!(
+    Cuboid => Cuboid,
+    Torus => Torus,
+    Cylinder => Cylinder,
+    RoundedCuboid => RoundedCuboid,
+    Capsule3d => Capsule,
+    Cone => Cone,
+    ConicalFrustum => ConicalFrustum,
+    Plane3d => Plane,
+    Panel => Panel,
+    CurvedPanel => CurvedPanel,
+    InfinitePlane3d => InfinitePlane,
+    Triangle3d => Triangle,
+    Tetrahedron => Tetrahedron,
+    Segment3d => Segment,
+    MeshField => Mesh,
+    CompositeField => Composite,
+    ModifiedField => Modified,
+);
@@ -0,0 +1,306 @@
+use std::fmt;
+
+use bevy::{
+    math::{
+        Vec3A,
+        bounding::{Aabb3d, BoundingVolume},
+    },
+    prelude::*,
+    reflect::{
+        ApplyError, FromReflect, FromType, GetTypeRegistration, PartialReflect, ReflectCloneError,
+        ReflectFromPtr, ReflectFromReflect, ReflectKind, ReflectMut, ReflectOwned, ReflectRef,
+        TupleStruct, TupleStructFieldIter, TupleStructInfo, TypeInfo, TypePath, TypeRegistration,
+        TypeRegistry, Typed, UnnamedField, tuple_struct_partial_eq,
+        utility::NonGenericTypeInfoCell,
+    },
+};
+
+use super::{Field, SignedDistance};
+
+/// A [`Field`] with its surface changed by a list of [`FieldModifier`]s, the first modifier is
+/// applied to the field directly and every other one to the result of the ones before it
+#[derive(Debug, Clone, Reflect)]
+// contains a field again, which the derived bounds can't handle
+#[reflect(Debug, Clone, no_field_bounds)]
+pub struct ModifiedField {
+    pub modifiers: Vec<FieldModifier>,
+    field: BoxedField,
+}
+
+/// The field inside of a [`ModifiedField`], reflected by hand as a tuple struct with the field as
+/// its only field, because `Box` can't be reflected
+#[derive(Debug, Clone, TypePath)]
+struct BoxedField(Box<Field>);
+
+#[derive(Debug, Clone, Copy, PartialEq, Reflect)]
+#[reflect(Debug, Clone, PartialEq)]
+pub enum FieldModifier {
+    /// grows the surface outwards by the radius, rounding off edges and corners
+    Inflate(f32),
+    /// hollows the field out into a shell of the thickness, centered on the surface
+    Shell(f32),
+    /// swaps inside and outside
+    Invert,
+    /// stretches the field apart at its local origin by these half lengths along each axis, exact
+    /// outside of the field
+    Elongate(Vec3),
+}
+
+impl ModifiedField {
+    pub fn new(field: Field, modifiers: impl IntoIterator<Item = FieldModifier>) -> Self {
+        Self {
+            modifiers: modifiers.into_iter().collect(),
+            field: BoxedField(Box::new(field)),
+        }
+    }
+    /// the field without any modifiers
+    pub fn field(&self) -> &Field {
+        &self.field.0
+    }
+    pub fn field_mut(&mut self) -> &mut Field {
+        &mut self.field.0
+    }
+    /// the bounds of just the surface, unlike [`SignedDistance::bounds`] these still exist for
+    /// inverted fields with a bounded surface
+    pub fn surface_bounds(&self) -> Option<Aabb3d> {
+        self.surface_bounds_with(&self.modifiers)
+    }
+    fn distance_with(&self, modifiers: &[FieldModifier], p: Vec3A) -> f32 {
+        let Some((modifier, rest)) = modifiers.split_last() else {
+            return self.field().local_distance(p);
+        };
+        match *modifier {
+            FieldModifier::Inflate(radius) => self.distance_with(rest, p) - radius,
+            FieldModifier::Shell(thickness) => {
+                self.distance_with(rest, p).abs() - thickness.abs() / 2.0
+            }
+            FieldModifier::Invert => -self.distance_with(rest, p),
+            FieldModifier::Elongate(half_lengths) => {
+                self.distance_with(rest, elongated_point(p, half_lengths))
+            }
+        }
+    }
+    fn normal_with(&self, modifiers: &[FieldModifier], p: Vec3A) -> Vec3A {
+        let Some((modifier, rest)) = modifiers.split_last() else {
+            return self.field().local_normal(p);
+        };
+        match *modifier {
+            FieldModifier::Inflate(_) => self.normal_with(rest, p),
+            // inside of the unmodified field the inner side of the shell is closer
+            FieldModifier::Shell(_) if self.distance_with(rest, p) < 0.0 => {
+                -self.normal_with(rest, p)
+            }
+            FieldModifier::Shell(_) => self.normal_with(rest, p),
+            FieldModifier::Invert => -self.normal_with(rest, p),
+            FieldModifier::Elongate(half_lengths) => {
+                self.normal_with(rest, elongated_point(p, half_lengths))
+            }
+        }
+    }
+    fn surface_bounds_with(&self, modifiers: &[FieldModifier]) -> Option<Aabb3d> {
+        let Some((modifier, rest)) = modifiers.split_last() else {
+            return self.field().local_bounds();
+        };
+        let bounds = self.surface_bounds_with(rest)?;
+        Some(match *modifier {
+            // inflating an inverted field moves the surface inwards, shrinking one outwards
+            FieldModifier::Inflate(radius) => bounds.grow(Vec3A::splat(radius.abs())),
+            FieldModifier::Shell(thickness) => bounds.grow(Vec3A::splat(thickness.abs() / 2.0)),
+            FieldModifier::Invert => bounds,
+            FieldModifier::Elongate(half_lengths) => bounds.grow(Vec3A::from(half_lengths.abs())),
+        })
+    }
+    /// whether everything outside of the surface is inside of the field, a shell only ever
+    /// contains the area around the surface
+    fn is_inverted(&self) -> bool {
+        self.modifiers
+            .iter()
+            .rev()
+            .take_while(|modifier| !matches!(modifier, FieldModifier::Shell(_)))
+            .filter(|modifier| matches!(modifier, FieldModifier::Invert))
+            .count()
+            % 2
+            == 1
+    }
+}
+
+/// where to evaluate the unmodified field, everything between `-half_lengths` and `half_lengths`
+/// collapses onto the origin
+fn elongated_point(p: Vec3A, half_lengths: Vec3) -> Vec3A {
+    let half_lengths = Vec3A::from(half_lengths.abs());
+    p - p.clamp(-half_lengths, half_lengths)
+}
+
+impl SignedDistance for ModifiedField {
+    fn distance(&self, p: Vec3A) -> f32 {
+        self.distance_with(&self.modifiers, p)
+    }
+    fn normal(&self, p: Vec3A) -> Vec3A {
+        self.normal_with(&self.modifiers, p)
+    }
+    /// `None` for inverted fields, their inside is unbounded
+    fn bounds(&self) -> Option<Aabb3d> {
+        if self.is_inverted() {
+            return None;
+        }
+        self.surface_bounds()
+    }
+}
+
+impl Field {
+    /// wraps this field in a [`ModifiedField`], or adds the modifier after the ones it already has
+    pub fn with_modifier(self, modifier: FieldModifier) -> Field {
+        match self {
+            Field::Modified(mut modified) => {
+                modified.modifiers.push(modifier);
+                Field::Modified(modified)
+            }
+            field => Field::Modified(ModifiedField::new(field, [modifier])),
+        }
+    }
+    /// see [`FieldModifier::Inflate`]
+    pub fn inflated(self, radius: f32) -> Field {
+        self.with_modifier(FieldModifier::Inflate(radius))
+    }
+    /// see [`FieldModifier::Shell`]
+    pub fn shelled(self, thickness: f32) -> Field {
+        self.with_modifier(FieldModifier::Shell(thickness))
+    }
+    /// see [`FieldModifier::Invert`]
+    pub fn inverted(self) -> Field {
+        self.with_modifier(FieldModifier::Invert)
+    }
+    /// see [`FieldModifier::Elongate`]
+    pub fn elongated(self, half_lengths: Vec3) -> Field {
+        self.with_modifier(FieldModifier::Elongate(half_lengths))
+    }
+}
+
+impl GetTypeRegistration for BoxedField {
+    fn get_type_registration() -> TypeRegistration {
+        let mut registration = TypeRegistration::of::<Self>();
+        registration.insert::<ReflectFromPtr>(FromType::<Self>::from_type());
+        registration.insert::<ReflectFromReflect>(FromType::<Self>::from_type());
+        registration
+    }
+    fn register_type_dependencies(registry: &mut TypeRegistry) {
+        registry.register::<Field>();
+    }
+}
+
+impl Typed for BoxedField {
+    fn type_info() -> &'static TypeInfo {
+        static CELL: NonGenericTypeInfoCell = NonGenericTypeInfoCell::new();
+        CELL.get_or_set(|| {
+            TypeInfo::TupleStruct(TupleStructInfo::new::<Self>(&[UnnamedField::new::<Field>(
+                0,
+            )]))
+        })
+    }
+}
+
+impl TupleStruct for BoxedField {
+    fn field(&self, index: usize) -> Option<&dyn PartialReflect> {
+        (index == 0).then_some(self.0.as_partial_reflect())
+    }
+    fn field_mut(&mut self, index: usize) -> Option<&mut dyn PartialReflect> {
+        (index == 0).then_some(self.0.as_partial_reflect_mut())
+    }
+    fn field_len(&self) -> usize {
+        1
+    }
+    fn iter_fields(&self) -> TupleStructFieldIter<'_> {
+        TupleStructFieldIter::new(self)
+    }
+}
+
+impl PartialReflect for BoxedField {
+    fn get_represented_type_info(&self) -> Option<&'static TypeInfo> {
+        Some(Self::type_info())
+    }
+    fn into_partial_reflect(self: Box<Self>) -> Box<dyn PartialReflect> {
+        self
+    }
+    fn as_partial_reflect(&self) -> &dyn PartialReflect {
+        self
+    }
+    fn as_partial_reflect_mut(&mut self) -> &mut dyn PartialReflect {
+        self
+    }
+    fn try_into_reflect(self: Box<Self>) -> Result<Box<dyn Reflect>, Box<dyn PartialReflect>> {
+        Ok(self)
+    }
+    fn try_as_reflect(&self) -> Option<&dyn Reflect> {
+        Some(self)
+    }
+    fn try_as_reflect_mut(&mut self) -> Option<&mut dyn Reflect> {
+        Some(self)
+    }
+    fn try_apply(&mut self, value: &dyn PartialReflect) -> Result<(), ApplyError> {
+        let ReflectRef::TupleStruct(value) = value.reflect_ref() else {
+            return Err(ApplyError::MismatchedKinds {
+                from_kind: value.reflect_kind(),
+                to_kind: ReflectKind::TupleStruct,
+            });
+        };
+        if let Some(field) = value.field(0) {
+            self.0.try_apply(field)?;
+        }
+        Ok(())
+    }
+    fn reflect_kind(&self) -> ReflectKind {
+        ReflectKind::TupleStruct
+    }
+    fn reflect_ref(&self) -> ReflectRef<'_> {
+        ReflectRef::TupleStruct(self)
+    }
+    fn reflect_mut(&mut self) -> ReflectMut<'_> {
+        ReflectMut::TupleStruct(self)
+    }
+    fn reflect_owned(self: Box<Self>) -> ReflectOwned {
+        ReflectOwned::TupleStruct(self)
+    }
+    fn reflect_clone(&self) -> Result<Box<dyn Reflect>, ReflectCloneError> {
+        Ok(Box::new(self.clone()))
+    }
+    fn reflect_partial_eq(&self, value: &dyn PartialReflect) -> Option<bool> {
+        tuple_struct_partial_eq(self, value)
+    }
+    fn debug(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+impl Reflect for BoxedField {
+    fn into_any(self: Box<Self>) -> Box<dyn std::any::Any> {
+        self
+    }
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+    fn into_reflect(self: Box<Self>) -> Box<dyn Reflect> {
+        self
+    }
+    fn as_reflect(&self) -> &dyn Reflect {
+        self
+    }
+    fn as_reflect_mut(&mut self) -> &mut dyn Reflect {
+        self
+    }
+    fn set(&mut self, value: Box<dyn Reflect>) -> Result<(), Box<dyn Reflect>> {
+        *self = *value.downcast()?;
+        Ok(())
+    }
+}
+
+impl FromReflect for BoxedField {
+    fn from_reflect(reflect: &dyn PartialReflect) -> Option<Self> {
+        let ReflectRef::TupleStruct(value) = reflect.reflect_ref() else {
+            return None;
+        };
+        Some(Self(Box::new(Field::from_reflect(value.field(0)?)?)))
+    }
+}
@@ -0,0 +1,1169 @@
+use std::f32::consts::{FRAC_PI_2, PI};
+
+use bevy::{
+    math::{
+        Vec3A,
+        bounding::{Aabb3d, Bounded3d},
+        vec3a,
+    },
+    prelude::*,
+};
+
+use super::{
+    RayIntersection, SignedDistance, SurfaceCoordinates, finite_difference_normal,
+    raycast::{
+        box_interval, convex_deepest_point, golden_section_min, overlap, quartic_roots,
+        sectioned_deepest_point, slab_interval, sphere_interval,
+    },
+};
+
+impl SignedDistance for Sphere {
+    fn distance(&self, p: Vec3A) -> f32 {
+        p.length() - self.radius
+    }
+    fn normal(&self, p: Vec3A) -> Vec3A {
+        p.normalize_or(Vec3A::Y)
+    }
+    fn closest_point(&self, p: Vec3A) -> Vec3A {
+        SignedDistance::normal(self, p) * self.radius
+    }
+    fn bounds(&self) -> Option<Aabb3d> {
+        Some(self.aabb_3d(Isometry3d::IDENTITY))
+    }
+    fn ray_intersection(&self, ray: Ray3d) -> Option<RayIntersection> {
+        let deepest = -ray.origin.dot(ray.direction.as_vec3());
+        Some(RayIntersection::new(
+            sphere_interval(ray, Vec3A::ZERO, self.radius),
+            deepest,
+        ))
+    }
+}
+
+impl SignedDistance for Cuboid {
+    fn distance(&self, p: Vec3A) -> f32 {
+        let q = p.abs() - Vec3A::from(self.half_size);
+        q.max(Vec3A::ZERO).length() + q.max_element().min(0.0)
+    }
+    fn normal(&self, p: Vec3A) -> Vec3A {
+        let half_size = Vec3A::from(self.half_size);
+        let clamped = p.clamp(-half_size, half_size);
+        if let Some(normal) = (p - clamped).try_normalize() {
+            return normal;
+        }
+        // inside or on the surface, the normal of the nearest face
+        let axis = nearest_face_axis(self, p);
+        axis * sign_or_positive(p.dot(axis))
+    }
+    fn closest_point(&self, p: Vec3A) -> Vec3A {
+        let half_size = Vec3A::from(self.half_size);
+        let clamped = p.clamp(-half_size, half_size);
+        if clamped != p {
+            return clamped;
+        }
+        // inside, project onto the nearest face
+        let axis = nearest_face_axis(self, p);
+        let face = half_size * axis * sign_or_positive(p.dot(axis));
+        p * (Vec3A::ONE - axis) + face
+    }
+    fn bounds(&self) -> Option<Aabb3d> {
+        Some(self.aabb_3d(Isometry3d::IDENTITY))
+    }
+    fn ray_intersection(&self, ray: Ray3d) -> Option<RayIntersection> {
+        let half_size = Vec3A::from(self.half_size);
+        Some(RayIntersection::new(
+            box_interval(ray, -half_size, half_size),
+            convex_deepest_point(self, ray),
+        ))
+    }
+}
+
+/// the unit axis along which the point is closest to a face of the cuboid
+fn nearest_face_axis(cuboid: &Cuboid, p: Vec3A) -> Vec3A {
+    let gap = Vec3A::from(cuboid.half_size) - p.abs();
+    if gap.x <= gap.y && gap.x <= gap.z {
+        Vec3A::X
+    } else if gap.y <= gap.z {
+        Vec3A::Y
+    } else {
+        Vec3A::Z
+    }
+}
+
+impl SignedDistance for Torus {
+    fn distance(&self, p: Vec3A) -> f32 {
+        let q = vec2(p.xz().length() - self.major_radius, p.y);
+        q.length() - self.minor_radius
+    }
+    fn normal(&self, p: Vec3A) -> Vec3A {
+        let ring_point = torus_ring_point(self, p);
+        (p - ring_point).normalize_or(ring_point.normalize_or(Vec3A::X))
+    }
+    fn closest_point(&self, p: Vec3A) -> Vec3A {
+        torus_ring_point(self, p) + SignedDistance::normal(self, p) * self.minor_radius
+    }
+    fn bounds(&self) -> Option<Aabb3d> {
+        Some(self.aabb_3d(Isometry3d::IDENTITY))
+    }
+    fn ray_intersection(&self, ray: Ray3d) -> Option<RayIntersection> {
+        let hit = torus_line_hits(self, ray)
+            .chunks_exact(2)
+            .map(|hit| (hit[0], hit[1]))
+            .find(|(_, exit)| *exit >= 0.0);
+        let deepest = match hit {
+            Some((entry, exit)) => golden_section_min(
+                |t| self.distance(ray.get_point(t).into()),
+                entry.max(0.0),
+                exit,
+            ),
+            // the distance can have a local minimum on either side of the axis
+            None => sectioned_deepest_point(self, ray, closest_to_vertical(ray, Vec2::ZERO)),
+        };
+        Some(RayIntersection::new(hit, deepest))
+    }
+}
+
+/// everywhere the line enters or leaves the torus in order, solving
+/// `(|p|² + R² - r²)² = 4R²(x² + z²)` for `p` along the line
+fn torus_line_hits(torus: &Torus, ray: Ray3d) -> Vec<f32> {
+    if sphere_interval(ray, Vec3A::ZERO, torus.outer_radius()).is_none() {
+        return Vec::new();
+    }
+    // starting where the line is closest to the center keeps the coefficients small
+    let start = -ray.origin.dot(ray.direction.as_vec3());
+    let origin = ray.get_point(start).as_dvec3();
+    let direction = ray.direction.as_vec3().as_dvec3();
+    let major_squared = (torus.major_radius as f64).powi(2);
+    let minor_squared = (torus.minor_radius as f64).powi(2);
+    let along = origin.dot(direction);
+    let k = origin.length_squared() + major_squared - minor_squared;
+    let a = direction.xz().length_squared();
+    let b = origin.xz().dot(direction.xz());
+    let c = origin.xz().length_squared();
+    quartic_roots([
+        4.0 * along,
+        4.0 * along * along + 2.0 * k - 4.0 * major_squared * a,
+        4.0 * along * k - 8.0 * major_squared * b,
+        k * k - 4.0 * major_squared * c,
+    ])
+    .into_iter()
+    .map(|t| t as f32 + start)
+    .collect()
+}
+
+/// where the line passes closest to the vertical line through `xz`, `None` when it's parallel to it
+fn closest_to_vertical(ray: Ray3d, xz: Vec2) -> Option<f32> {
+    let direction = ray.direction.as_vec3().xz();
+    let t = (xz - ray.origin.xz()).dot(direction) / direction.length_squared();
+    t.is_finite().then_some(t)
+}
+
+/// the closest point on the circle running through the middle of the torus tube
+fn torus_ring_point(torus: &Torus, p: Vec3A) -> Vec3A {
+    let radial = vec3a(p.x, 0.0, p.z).normalize_or(Vec3A::X);
+    radial * torus.major_radius
+}
+
+impl SignedDistance for Cylinder {
+    fn distance(&self, p: Vec3A) -> f32 {
+        let d = vec2(p.xz().length() - self.radius, p.y.abs() - self.half_height);
+        d.x.max(d.y).min(0.0) + d.max(Vec2::ZERO).length()
+    }
+    fn normal(&self, p: Vec3A) -> Vec3A {
+        let (d, radial, cap) = cylinder_parts(self, p);
+        if d.x > 0.0 && d.y > 0.0 {
+            (p - (radial * self.radius + cap * self.half_height)).normalize_or(radial)
+        } else if d.x > d.y {
+            radial
+        } else {
+            cap
+        }
+    }
+    fn closest_point(&self, p: Vec3A) -> Vec3A {
+        let (d, radial, cap) = cylinder_parts(self, p);
+        let side = radial * self.radius;
+        let cap_height = cap * self.half_height;
+        if d.x > 0.0 && d.y > 0.0 {
+            side + cap_height
+        } else if d.x > d.y {
+            side + Vec3A::Y * p.y
+        } else {
+            vec3a(p.x, 0.0, p.z) + cap_height
+        }
+    }
+    fn bounds(&self) -> Option<Aabb3d> {
+        Some(self.aabb_3d(Isometry3d::IDENTITY))
+    }
+    fn ray_intersection(&self, ray: Ray3d) -> Option<RayIntersection> {
+        Some(RayIntersection::new(
+            cylinder_interval(ray, self.radius, self.half_height),
+            convex_deepest_point(self, ray),
+        ))
+    }
+}
+
+/// where the line enters and leaves a cylinder around the `Y` axis
+fn cylinder_interval(ray: Ray3d, radius: f32, half_height: f32) -> Option<(f32, f32)> {
+    let origin = Vec3A::from(ray.origin);
+    let direction = Vec3A::from(ray.direction.as_vec3());
+    let height = slab_interval(origin.y, direction.y, -half_height, half_height)?;
+    let a = direction.xz().length_squared();
+    let b = origin.xz().dot(direction.xz());
+    let c = origin.xz().length_squared() - radius * radius;
+    if a == 0.0 {
+        // parallel to the axis
+        return (c <= 0.0).then_some(height);
+    }
+    let discriminant = b * b - a * c;
+    if discriminant < 0.0 {
+        return None;
+    }
+    let root = discriminant.sqrt();
+    overlap(((-b - root) / a, (-b + root) / a), height)
+}
+
+/// the 2d distance to the side and cap, the outward direction of the side and of the nearest cap
+fn cylinder_parts(cylinder: &Cylinder, p: Vec3A) -> (Vec2, Vec3A, Vec3A) {
+    let d = vec2(
+        p.xz().length() - cylinder.radius,
+        p.y.abs() - cylinder.half_height,
+    );
+    let radial = vec3a(p.x, 0.0, p.z).normalize_or(Vec3A::X);
+    let cap = Vec3A::Y * sign_or_positive(p.y);
+    (d, radial, cap)
+}
+
+/// like [`f32::signum`] but doesn't care about the sign of zero
+fn sign_or_positive(v: f32) -> f32 {
+    if v < 0.0 { -1.0 } else { 1.0 }
+}
+
+/// A cuboid with its edges and corners rounded off, `half_size` includes the rounding
+#[derive(Debug, Clone, Copy, PartialEq, Reflect)]
+#[reflect(Debug, Clone, PartialEq)]
+pub struct RoundedCuboid {
+    pub half_size: Vec3,
+    pub radius: f32,
+}
+
+impl RoundedCuboid {
+    pub fn new(x_length: f32, y_length: f32, z_length: f32, radius: f32) -> Self {
+        Self {
+            half_size: Vec3::new(x_length, y_length, z_length) / 2.0,
+            radius,
+        }
+    }
+    /// the cuboid that gets rounded off
+    pub fn inner_cuboid(&self) -> Cuboid {
+        Cuboid {
+            half_size: (self.half_size - self.radius).max(Vec3::ZERO),
+        }
+    }
+}
+
+impl SignedDistance for RoundedCuboid {
+    fn distance(&self, p: Vec3A) -> f32 {
+        SignedDistance::distance(&self.inner_cuboid(), p) - self.radius
+    }
+    fn normal(&self, p: Vec3A) -> Vec3A {
+        SignedDistance::normal(&self.inner_cuboid(), p)
+    }
+    fn closest_point(&self, p: Vec3A) -> Vec3A {
+        let inner = self.inner_cuboid();
+        SignedDistance::closest_point(&inner, p) + SignedDistance::normal(&inner, p) * self.radius
+    }
+    fn bounds(&self) -> Option<Aabb3d> {
+        Some(Aabb3d::new(Vec3A::ZERO, self.half_size))
+    }
+    fn ray_intersection(&self, ray: Ray3d) -> Option<RayIntersection> {
+        let size = Vec3A::from(self.inner_cuboid().half_size);
+        let reversed = Ray3d {
+            origin: ray.origin,
+            direction: -ray.direction,
+        };
+        // leaving the shape going forward is entering it going backwards
+        let hit = rounded_cuboid_entry(ray, size, self.radius)
+            .zip(rounded_cuboid_entry(reversed, size, self.radius).map(|t| -t));
+        Some(RayIntersection::new(hit, convex_deepest_point(self, ray)))
+    }
+}
+
+/// where the line first enters the cuboid with a half size of `size` grown by `radius`, adapted
+/// from the rounded box intersection by Inigo Quilez
+fn rounded_cuboid_entry(ray: Ray3d, size: Vec3A, radius: f32) -> Option<f32> {
+    let (entry, _) = box_interval(ray, -size - radius, size + radius)?;
+    // mirrored into the corner the line enters the bounding box at
+    let mirror = Vec3A::from(ray.get_point(entry)).signum();
+    let origin = Vec3A::from(ray.origin) * mirror;
+    let direction = Vec3A::from(ray.direction.as_vec3()) * mirror;
+    let on_bounds = origin + direction * entry - size;
+    if on_bounds.max(on_bounds.yzx()).min_element() < 0.0 {
+        // on one of the flat faces
+        return Some(entry);
+    }
+    let offset = origin - size;
+    let radius_squared = radius * radius;
+    let corner = {
+        let b = offset.dot(direction);
+        let discriminant = b * b - (offset.length_squared() - radius_squared);
+        (discriminant >= 0.0).then(|| -b - discriminant.sqrt())
+    };
+    // the edges of the corner, each along one axis
+    let edges = (0..3).filter_map(|axis| {
+        let mut across = Vec3A::ONE;
+        across[axis] = 0.0;
+        let (offset, along) = (offset * across, direction * across);
+        let a = along.length_squared();
+        let b = offset.dot(along);
+        let discriminant = b * b - a * (offset.length_squared() - radius_squared);
+        if a == 0.0 || discriminant < 0.0 {
+            return None;
+        }
+        let t = (-b - discriminant.sqrt()) / a;
+        ((origin[axis] + direction[axis] * t).abs() <= size[axis]).then_some(t)
+    });
+    corner.into_iter().chain(edges).min_by(f32::total_cmp)
+}
+
+/// A flat rectangle without thickness on the local `XY` plane, facing `+Z` like a [`Rectangle`]
+/// mesh, with optionally rounded corners. Has [`SurfaceCoordinates`] for world-space UI.
+#[derive(Debug, Clone, Copy, PartialEq, Reflect)]
+#[reflect(Debug, Clone, PartialEq)]
+pub struct Panel {
+    pub half_size: Vec2,
+    /// clamped to the smaller side of `half_size`
+    pub corner_radius: f32,
+}
+
+impl Panel {
+    pub fn new(width: f32, height: f32) -> Self {
+        Self {
+            half_size: Vec2::new(width, height) / 2.0,
+            corner_radius: 0.0,
+        }
+    }
+    pub fn with_corner_radius(mut self, corner_radius: f32) -> Self {
+        self.corner_radius = corner_radius;
+        self
+    }
+    /// the coordinates of the point on the panel closest to `p`, which is in local space
+    pub fn surface_coordinates(&self, p: Vec3A) -> SurfaceCoordinates {
+        let local = self.closest_point_2d(p.xy());
+        SurfaceCoordinates {
+            local,
+            uv: Vec2::new(0.5, 0.5) + local * Vec2::new(0.5, -0.5) / self.half_size,
+        }
+    }
+    fn radius(&self) -> f32 {
+        self.corner_radius.clamp(0.0, self.half_size.min_element())
+    }
+    /// the rectangle the corners get rounded around
+    fn inner_half_size(&self) -> Vec2 {
+        self.half_size - self.radius()
+    }
+    fn closest_point_2d(&self, p: Vec2) -> Vec2 {
+        let inner = self.inner_half_size();
+        let clamped = p.clamp(-inner, inner);
+        clamped + (p - clamped).clamp_length_max(self.radius())
+    }
+    fn contains_2d(&self, p: Vec2) -> bool {
+        (p.abs() - self.inner_half_size()).max(Vec2::ZERO).length() <= self.radius()
+    }
+}
+
+/// zero thickness, so the distance is never negative
+impl SignedDistance for Panel {
+    fn distance(&self, p: Vec3A) -> f32 {
+        p.distance(SignedDistance::closest_point(self, p))
+    }
+    fn normal(&self, p: Vec3A) -> Vec3A {
+        (p - SignedDistance::closest_point(self, p))
+            .try_normalize()
+            .unwrap_or(Vec3A::Z * sign_or_positive(p.z))
+    }
+    fn closest_point(&self, p: Vec3A) -> Vec3A {
+        self.closest_point_2d(p.xy()).extend(0.0).into()
+    }
+    fn bounds(&self) -> Option<Aabb3d> {
+        Some(Aabb3d::new(Vec3A::ZERO, self.half_size.extend(0.0)))
+    }
+    fn ray_intersection(&self, ray: Ray3d) -> Option<RayIntersection> {
+        let t = -ray.origin.z / ray.direction.z;
+        let point = ray.get_point(t).xy();
+        let hit = (t.is_finite() && self.contains_2d(point)).then_some((t, t));
+        let deepest = hit.map_or_else(|| convex_deepest_point(self, ray), |(t, _)| t);
+        Some(RayIntersection::new(hit, deepest))
+    }
+}
+
+/// A section of a cylindrical shell around the local `Y` axis for curved world-space UI. The
+/// middle of the arc is on `-Z`, so it wraps around a viewer at the origin. Has
+/// [`SurfaceCoordinates`] along the arc.
+#[derive(Debug, Clone, Copy, PartialEq, Reflect)]
+#[reflect(Debug, Clone, PartialEq)]
+pub struct CurvedPanel {
+    /// from the axis to the middle of the shell
+    pub radius: f32,
+    /// how far the panel wraps around the axis in radians, up to `2π`
+    pub arc_angle: f32,
+    pub height: f32,
+    pub thickness: f32,
+}
+
+impl CurvedPanel {
+    pub fn new(radius: f32, arc_angle: f32, height: f32, thickness: f32) -> Self {
+        Self {
+            radius,
+            arc_angle,
+            height,
+            thickness,
+        }
+    }
+    /// the angle from the middle of the arc to either end
+    pub fn half_angle(&self) -> f32 {
+        (self.arc_angle / 2.0).clamp(0.0, PI)
+    }
+    /// `angle` goes from the middle of the arc towards `+X`
+    pub fn point(&self, angle: f32, radius: f32, y: f32) -> Vec3A {
+        let (sin, cos) = angle.sin_cos();
+        vec3a(sin * radius, y, -cos * radius)
+    }
+    /// the coordinates of the point in the middle of the shell closest to `p`, which is in local
+    /// space. The arc length is used for the local `X` coordinate.
+    pub fn surface_coordinates(&self, p: Vec3A) -> SurfaceCoordinates {
+        let half_angle = self.half_angle();
+        let half_height = self.height / 2.0;
+        let angle = p.x.atan2(-p.z).clamp(-half_angle, half_angle);
+        let y = p.y.clamp(-half_height, half_height);
+        SurfaceCoordinates {
+            local: Vec2::new(angle * self.radius, y),
+            uv: Vec2::new(
+                (angle + half_angle) / (2.0 * half_angle),
+                (half_height - y) / self.height,
+            ),
+        }
+    }
+    /// whether `p` is within the height and the arc, at any distance from the axis
+    fn spans(&self, p: Vec3A) -> bool {
+        p.y.abs() <= self.height / 2.0 && p.x.atan2(-p.z).abs() <= self.half_angle()
+    }
+    /// everywhere the line crosses the cylinders, the caps or the ends of the arc in order
+    fn line_crossings(&self, ray: Ray3d) -> Vec<f32> {
+        let half_thickness = self.thickness / 2.0;
+        let half_height = self.height / 2.0;
+        let origin = Vec3A::from(ray.origin);
+        let direction = Vec3A::from(ray.direction.as_vec3());
+        let cylinders = [self.radius - half_thickness, self.radius + half_thickness]
+            .into_iter()
+            .filter_map(|radius| cylinder_interval(ray, radius, f32::INFINITY));
+        let caps = slab_interval(origin.y, direction.y, -half_height, half_height);
+        let ends = [-1.0, 1.0].map(|side| {
+            let end = self.point(self.half_angle() * side, 1.0, 0.0);
+            let end_normal = vec3a(-end.z, 0.0, end.x);
+            -origin.dot(end_normal) / direction.dot(end_normal)
+        });
+        let mut crossings = cylinders
+            .chain(caps)
+            .flat_map(|(entry, exit)| [entry, exit])
+            .chain(ends)
+            .filter(|t| t.is_finite())
+            .collect::<Vec<_>>();
+        crossings.sort_by(f32::total_cmp);
+        crossings
+    }
+}
+
+impl SignedDistance for CurvedPanel {
+    fn distance(&self, p: Vec3A) -> f32 {
+        // a ring section in the XZ plane, mirrored around the middle of the arc and rotated so the
+        // end of the arc lies on `+Y`
+        let (sin, cos) = self.half_angle().sin_cos();
+        let q = vec2(p.x.abs(), -p.z);
+        let q = vec2(cos * q.x - sin * q.y, sin * q.x + cos * q.y);
+        let half_thickness = self.thickness / 2.0;
+        let ring = (q.length() - self.radius).abs() - half_thickness;
+        let end = vec2(q.x, ((self.radius - q.y).abs() - half_thickness).max(0.0)).length()
+            * sign_or_positive(q.x);
+        // extruded along Y
+        let d = vec2(ring.max(end), p.y.abs() - self.height / 2.0);
+        d.x.max(d.y).min(0.0) + d.max(Vec2::ZERO).length()
+    }
+    fn normal(&self, p: Vec3A) -> Vec3A {
+        let offset = p - SignedDistance::closest_point(self, p);
+        let outward = if self.distance(p) < 0.0 {
+            -offset
+        } else {
+            offset
+        };
+        outward
+            .try_normalize()
+            .unwrap_or_else(|| finite_difference_normal(|p| self.distance(p), p))
+    }
+    fn closest_point(&self, p: Vec3A) -> Vec3A {
+        let half_angle = self.half_angle();
+        let half_height = self.height / 2.0;
+        let inner = self.radius - self.thickness / 2.0;
+        let outer = self.radius + self.thickness / 2.0;
+        let angle = p.x.atan2(-p.z);
+        // the direction of the end of the arc on the same side as `p`
+        let end = self.point(half_angle.copysign(angle), 1.0, 0.0);
+        let on_end = end * p.dot(end).clamp(inner, outer);
+        if self.distance(p) > 0.0 {
+            // the shell is the ring section extruded along Y, so the closest point can be found for
+            // both separately
+            let flat = if angle.abs() <= half_angle {
+                self.point(angle, p.xz().length().clamp(inner, outer), 0.0)
+            } else {
+                on_end
+            };
+            return flat.with_y(p.y.clamp(-half_height, half_height));
+        }
+        [
+            self.point(angle, inner, p.y),
+            self.point(angle, outer, p.y),
+            p.with_y(half_height),
+            p.with_y(-half_height),
+            on_end.with_y(p.y),
+        ]
+        .into_iter()
+        .min_by(|a, b| p.distance_squared(*a).total_cmp(&p.distance_squared(*b)))
+        .unwrap()
+    }
+    fn bounds(&self) -> Option<Aabb3d> {
+        let half_angle = self.half_angle();
+        let half_height = self.height / 2.0;
+        let half_thickness = self.thickness / 2.0;
+        let mut points = Vec::new();
+        // the ends of the arc and every axis direction the arc passes through
+        for angle in [-half_angle, half_angle, 0.0, -FRAC_PI_2, FRAC_PI_2, PI] {
+            if angle.abs() > half_angle {
+                continue;
+            }
+            for radius in [self.radius - half_thickness, self.radius + half_thickness] {
+                for y in [-half_height, half_height] {
+                    points.push(self.point(angle, radius, y));
+                }
+            }
+        }
+        Some(Aabb3d::from_point_cloud(
+            Isometry3d::IDENTITY,
+            points.into_iter(),
+        ))
+    }
+    fn ray_intersection(&self, ray: Ray3d) -> Option<RayIntersection> {
+        let crossings = self.line_crossings(ray);
+        let hit = if self.thickness <= 0.0 {
+            // without thickness the line only touches the shell where it crosses the cylinder
+            cylinder_interval(ray, self.radius, f32::INFINITY)
+                .into_iter()
+                .flat_map(|(entry, exit)| [entry, exit])
+                .find(|t| *t >= 0.0 && t.is_finite() && self.spans(ray.get_point(*t).into()))
+                .map(|t| (t, t))
+        } else {
+            // the shape stays the same between crossings, so checking the middle is enough
+            let mut hit = None;
+            for section in crossings.windows(2) {
+                let (start, end) = (section[0], section[1]);
+                let middle = Vec3A::from(ray.get_point((start + end) / 2.0));
+                let inside = (middle.xz().length() - self.radius).abs() <= self.thickness / 2.0
+                    && self.spans(middle);
+                match (inside, &mut hit) {
+                    (true, Some((_, exit))) => *exit = end,
+                    (true, None) => hit = Some((start, end)),
+                    (false, Some((_, exit))) if *exit >= 0.0 => break,
+                    (false, _) => hit = None,
+                }
+            }
+            hit
+        };
+        let deepest = match hit {
+            Some((entry, exit)) => golden_section_min(
+                |t| self.distance(ray.get_point(t).into()),
+                entry.max(0.0),
+                exit,
+            ),
+            None => {
+                // the axis and the ends of the arc
+                let ends =
+                    [-1.0, 1.0].map(|side| self.point(self.half_angle() * side, self.radius, 0.0));
+                let splits = [Vec3A::ZERO, ends[0], ends[1]]
+                    .into_iter()
+                    .filter_map(|point| closest_to_vertical(ray, point.xz()));
+                sectioned_deepest_point(self, ray, crossings.into_iter().chain(splits))
+            }
+        };
+        Some(RayIntersection::new(hit, deepest))
+    }
+}
+
+impl SignedDistance for Capsule3d {
+    fn distance(&self, p: Vec3A) -> f32 {
+        p.distance(capsule_axis_point(self, p)) - self.radius
+    }
+    fn normal(&self, p: Vec3A) -> Vec3A {
+        (p - capsule_axis_point(self, p)).normalize_or(Vec3A::X)
+    }
+    fn closest_point(&self, p: Vec3A) -> Vec3A {
+        capsule_axis_point(self, p) + SignedDistance::normal(self, p) * self.radius
+    }
+    fn bounds(&self) -> Option<Aabb3d> {
+        Some(self.aabb_3d(Isometry3d::IDENTITY))
+    }
+    fn ray_intersection(&self, ray: Ray3d) -> Option<RayIntersection> {
+        let cap = Vec3A::Y * self.half_length;
+        // the capsule is convex, so the parts the line passes through are connected
+        let hit = [
+            cylinder_interval(ray, self.radius, self.half_length),
+            sphere_interval(ray, cap, self.radius),
+            sphere_interval(ray, -cap, self.radius),
+        ]
+        .into_iter()
+        .flatten()
+        .reduce(|a, b| (a.0.min(b.0), a.1.max(b.1)));
+        Some(RayIntersection::new(hit, convex_deepest_point(self, ray)))
+    }
+}
+
+fn capsule_axis_point(capsule: &Capsule3d, p: Vec3A) -> Vec3A {
+    Vec3A::Y * p.y.clamp(-capsule.half_length, capsule.half_length)
+}
+
+impl SignedDistance for Segment3d {
+    fn distance(&self, p: Vec3A) -> f32 {
+        p.distance(closest_point_on_segment(self, p))
+    }
+    fn normal(&self, p: Vec3A) -> Vec3A {
+        let direction = Vec3A::from(self.vertices[1] - self.vertices[0]).normalize_or(Vec3A::Y);
+        (p - closest_point_on_segment(self, p)).normalize_or(direction.any_orthonormal_vector())
+    }
+    fn closest_point(&self, p: Vec3A) -> Vec3A {
+        closest_point_on_segment(self, p)
+    }
+    fn bounds(&self) -> Option<Aabb3d> {
+        Some(self.aabb_3d(Isometry3d::IDENTITY))
+    }
+    /// never hits, there's nothing to hit without thickness
+    fn ray_intersection(&self, ray: Ray3d) -> Option<RayIntersection> {
+        Some(RayIntersection::new(None, convex_deepest_point(self, ray)))
+    }
+}
+
+fn closest_point_on_segment(segment: &Segment3d, p: Vec3A) -> Vec3A {
+    let [a, b] = segment.vertices.map(Vec3A::from);
+    let ab = b - a;
+    let t = ((p - a).dot(ab) / ab.length_squared()).clamp(0.0, 1.0);
+    // a zero length segment gives a NaN
+    if t.is_nan() { a } else { a + ab * t }
+}
+
+impl SignedDistance for Cone {
+    fn distance(&self, p: Vec3A) -> f32 {
+        RevolvedProfile::cone(self).distance(p)
+    }
+    fn normal(&self, p: Vec3A) -> Vec3A {
+        RevolvedProfile::cone(self).normal(p)
+    }
+    fn closest_point(&self, p: Vec3A) -> Vec3A {
+        RevolvedProfile::cone(self).closest_point(p)
+    }
+    fn bounds(&self) -> Option<Aabb3d> {
+        Some(self.aabb_3d(Isometry3d::IDENTITY))
+    }
+    fn ray_intersection(&self, ray: Ray3d) -> Option<RayIntersection> {
+        Some(RayIntersection::new(
+            RevolvedProfile::cone(self).line_interval(ray),
+            convex_deepest_point(self, ray),
+        ))
+    }
+}
+
+impl SignedDistance for ConicalFrustum {
+    fn distance(&self, p: Vec3A) -> f32 {
+        RevolvedProfile::frustum(self).distance(p)
+    }
+    fn normal(&self, p: Vec3A) -> Vec3A {
+        RevolvedProfile::frustum(self).normal(p)
+    }
+    fn closest_point(&self, p: Vec3A) -> Vec3A {
+        RevolvedProfile::frustum(self).closest_point(p)
+    }
+    fn bounds(&self) -> Option<Aabb3d> {
+        Some(self.aabb_3d(Isometry3d::IDENTITY))
+    }
+    fn ray_intersection(&self, ray: Ray3d) -> Option<RayIntersection> {
+        Some(RayIntersection::new(
+            RevolvedProfile::frustum(self).line_interval(ray),
+            convex_deepest_point(self, ray),
+        ))
+    }
+}
+
+/// A convex outline in the (radius, height) plane that gets revolved around the `Y` axis, running
+/// from the bottom center over the bottom and top radius to the top center
+struct RevolvedProfile([Vec2; 4]);
+
+impl RevolvedProfile {
+    fn cone(cone: &Cone) -> Self {
+        Self::frustum(&ConicalFrustum {
+            radius_top: 0.0,
+            radius_bottom: cone.radius,
+            height: cone.height,
+        })
+    }
+    fn frustum(frustum: &ConicalFrustum) -> Self {
+        let half_height = frustum.height / 2.0;
+        Self([
+            vec2(0.0, -half_height),
+            vec2(frustum.radius_bottom, -half_height),
+            vec2(frustum.radius_top, half_height),
+            vec2(0.0, half_height),
+        ])
+    }
+    /// the closest point on the outline and the outward normal of the edge it's on, in the
+    /// (radius, height) plane
+    fn closest_on_outline(&self, q: Vec2) -> (Vec2, Vec2) {
+        self.0
+            .windows(2)
+            .filter(|edge| edge[0] != edge[1])
+            .map(|edge| {
+                let (a, b) = (edge[0], edge[1]);
+                let ab = b - a;
+                let t = ((q - a).dot(ab) / ab.length_squared()).clamp(0.0, 1.0);
+                (a + ab * t, vec2(ab.y, -ab.x).normalize())
+            })
+            .min_by(|(c1, _), (c2, _)| q.distance_squared(*c1).total_cmp(&q.distance_squared(*c2)))
+            .unwrap_or((Vec2::ZERO, Vec2::Y))
+    }
+    fn is_inside(&self, q: Vec2) -> bool {
+        self.0
+            .windows(2)
+            .filter(|edge| edge[0] != edge[1])
+            .all(|edge| {
+                (q - edge[0]).dot(vec2(edge[1].y - edge[0].y, edge[0].x - edge[1].x)) <= 0.0
+            })
+    }
+    /// the point in the (radius, height) plane and the outward direction it's radius is measured in
+    fn project(p: Vec3A) -> (Vec2, Vec3A) {
+        (
+            vec2(p.xz().length(), p.y),
+            vec3a(p.x, 0.0, p.z).normalize_or(Vec3A::X),
+        )
+    }
+    fn distance(&self, p: Vec3A) -> f32 {
+        let (q, _) = Self::project(p);
+        let (closest, _) = self.closest_on_outline(q);
+        let distance = q.distance(closest);
+        if self.is_inside(q) {
+            -distance
+        } else {
+            distance
+        }
+    }
+    fn normal(&self, p: Vec3A) -> Vec3A {
+        let (q, radial) = Self::project(p);
+        let (closest, edge_normal) = self.closest_on_outline(q);
+        let sign = if self.is_inside(q) { -1.0 } else { 1.0 };
+        let normal = ((q - closest) * sign)
+            .try_normalize()
+            .unwrap_or(edge_normal);
+        radial * normal.x + Vec3A::Y * normal.y
+    }
+    fn closest_point(&self, p: Vec3A) -> Vec3A {
+        let (q, radial) = Self::project(p);
+        let (closest, _) = self.closest_on_outline(q);
+        radial * closest.x + Vec3A::Y * closest.y
+    }
+    /// where the line enters and leaves the frustum between the bottom and top radius, which is
+    /// where it's inside of both the height and the infinite double cone the side lies on
+    fn line_interval(&self, ray: Ray3d) -> Option<(f32, f32)> {
+        let [_, bottom, top, _] = self.0;
+        let origin = Vec3A::from(ray.origin);
+        let direction = Vec3A::from(ray.direction.as_vec3());
+        let height = slab_interval(origin.y, direction.y, bottom.y, top.y)?;
+        // the radius of the cone changes linearly with the height
+        let slope = (top.x - bottom.x) / (top.y - bottom.y);
+        let radius = bottom.x + slope * (origin.y - bottom.y);
+        let radius_along = slope * direction.y;
+        // inside where `a t² + 2 b t + c <= 0`
+        let a = direction.xz().length_squared() - radius_along * radius_along;
+        let b = origin.xz().dot(direction.xz()) - radius * radius_along;
+        let c = origin.xz().length_squared() - radius * radius;
+        let discriminant = b * b - a * c;
+        let cone = if a == 0.0 {
+            // parallel to the side, so only crosses it once
+            let t = -c / (2.0 * b);
+            if b > 0.0 {
+                [Some((f32::NEG_INFINITY, t)), None]
+            } else if b < 0.0 {
+                [Some((t, f32::INFINITY)), None]
+            } else {
+                [
+                    (c <= 0.0).then_some((f32::NEG_INFINITY, f32::INFINITY)),
+                    None,
+                ]
+            }
+        } else if discriminant < 0.0 {
+            // misses the cone, or stays inside of it
+            [
+                (a < 0.0).then_some((f32::NEG_INFINITY, f32::INFINITY)),
+                None,
+            ]
+        } else {
+            let root = discriminant.sqrt();
+            let (t1, t2) = ((-b - root) / a, (-b + root) / a);
+            let (near, far) = (t1.min(t2), t1.max(t2));
+            if a > 0.0 {
+                [Some((near, far)), None]
+            } else {
+                // crosses both halves of the double cone, only one of them is within the height
+                [Some((f32::NEG_INFINITY, near)), Some((far, f32::INFINITY))]
+            }
+        };
+        cone.into_iter()
+            .flatten()
+            .filter_map(|cone| overlap(cone, height))
+            .reduce(|a, b| (a.0.min(b.0), a.1.max(b.1)))
+    }
+}
+
+impl SignedDistance for InfinitePlane3d {
+    fn distance(&self, p: Vec3A) -> f32 {
+        p.dot(self.normal.as_vec3().into())
+    }
+    fn normal(&self, _p: Vec3A) -> Vec3A {
+        self.normal.as_vec3().into()
+    }
+    fn ray_intersection(&self, ray: Ray3d) -> Option<RayIntersection> {
+        let height = ray.origin.dot(self.normal.as_vec3());
+        let along = ray.direction.dot(self.normal.as_vec3());
+        let hit = slab_interval(height, along, f32::NEG_INFINITY, 0.0);
+        // keeps getting deeper when heading into the plane, so the entry is used instead
+        let deepest = hit.filter(|_| along < 0.0).map_or(0.0, |(entry, _)| entry);
+        Some(RayIntersection::new(hit, deepest))
+    }
+}
+
+/// zero thickness, so the distance is never negative
+impl SignedDistance for Plane3d {
+    fn distance(&self, p: Vec3A) -> f32 {
+        p.distance(SignedDistance::closest_point(self, p))
+    }
+    fn normal(&self, p: Vec3A) -> Vec3A {
+        let normal = Vec3A::from(self.normal.as_vec3());
+        let side = sign_or_positive(p.dot(normal));
+        (p - SignedDistance::closest_point(self, p))
+            .try_normalize()
+            .unwrap_or(normal * side)
+    }
+    fn closest_point(&self, p: Vec3A) -> Vec3A {
+        let rotation = plane_rotation(self);
+        let local = rotation.inverse() * p;
+        let clamped = vec3a(
+            local.x.clamp(-self.half_size.x, self.half_size.x),
+            0.0,
+            local.z.clamp(-self.half_size.y, self.half_size.y),
+        );
+        rotation * clamped
+    }
+    fn bounds(&self) -> Option<Aabb3d> {
+        let half_size = vec3(self.half_size.x, 0.0, self.half_size.y);
+        let mirrored = half_size * vec3(1.0, 0.0, -1.0);
+        let corners = [half_size, -half_size, mirrored, -mirrored];
+        Some(Aabb3d::from_point_cloud(
+            plane_rotation(self),
+            corners.into_iter(),
+        ))
+    }
+    fn ray_intersection(&self, ray: Ray3d) -> Option<RayIntersection> {
+        let normal = self.normal.as_vec3();
+        let t = -ray.origin.dot(normal) / ray.direction.dot(normal);
+        let local = plane_rotation(self).inverse() * ray.get_point(t);
+        let hit = (t.is_finite()
+            && local.x.abs() <= self.half_size.x
+            && local.z.abs() <= self.half_size.y)
+            .then_some((t, t));
+        let deepest = hit.map_or_else(|| convex_deepest_point(self, ray), |(t, _)| t);
+        Some(RayIntersection::new(hit, deepest))
+    }
+}
+
+/// same orientation as the plane mesh, spanning the local X and Z axes before the rotation
+fn plane_rotation(plane: &Plane3d) -> Quat {
+    Quat::from_rotation_arc(Vec3::Y, plane.normal.as_vec3())
+}
+
+/// zero thickness, so the distance is never negative
+impl SignedDistance for Triangle3d {
+    fn distance(&self, p: Vec3A) -> f32 {
+        p.distance(SignedDistance::closest_point(self, p))
+    }
+    fn normal(&self, p: Vec3A) -> Vec3A {
+        let [a, b, c] = self.vertices.map(Vec3A::from);
+        let face_normal = (b - a).cross(c - a).normalize_or(Vec3A::Y);
+        let side = sign_or_positive((p - a).dot(face_normal));
+        (p - SignedDistance::closest_point(self, p))
+            .try_normalize()
+            .unwrap_or(face_normal * side)
+    }
+    fn closest_point(&self, p: Vec3A) -> Vec3A {
+        let [a, b, c] = self.vertices.map(Vec3A::from);
+        closest_point_on_triangle(p, a, b, c)
+    }
+    fn bounds(&self) -> Option<Aabb3d> {
+        Some(self.aabb_3d(Isometry3d::IDENTITY))
+    }
+    fn ray_intersection(&self, ray: Ray3d) -> Option<RayIntersection> {
+        let hit = triangle_line_intersection(ray, self.vertices.map(Vec3A::from)).map(|t| (t, t));
+        let deepest = hit.map_or_else(|| convex_deepest_point(self, ray), |(t, _)| t);
+        Some(RayIntersection::new(hit, deepest))
+    }
+}
+
+/// Möller–Trumbore, along the whole line and from both sides of the triangle
+fn triangle_line_intersection(ray: Ray3d, [a, b, c]: [Vec3A; 3]) -> Option<f32> {
+    let direction = Vec3A::from(ray.direction.as_vec3());
+    let ab = b - a;
+    let ac = c - a;
+    let p = direction.cross(ac);
+    let determinant = ab.dot(p);
+    if determinant == 0.0 {
+        return None;
+    }
+    let ao = Vec3A::from(ray.origin) - a;
+    let u = ao.dot(p) / determinant;
+    let q = ao.cross(ab);
+    let v = direction.dot(q) / determinant;
+    (u >= 0.0 && v >= 0.0 && u + v <= 1.0).then(|| ac.dot(q) / determinant)
+}
+
+impl SignedDistance for Tetrahedron {
+    fn distance(&self, p: Vec3A) -> f32 {
+        let (closest, _, inside) = tetrahedron_closest_face(self, p);
+        let distance = p.distance(closest);
+        if inside { -distance } else { distance }
+    }
+    fn normal(&self, p: Vec3A) -> Vec3A {
+        let (closest, face_normal, inside) = tetrahedron_closest_face(self, p);
+        let sign = if inside { -1.0 } else { 1.0 };
+        ((p - closest) * sign)
+            .try_normalize()
+            .unwrap_or(face_normal)
+    }
+    fn closest_point(&self, p: Vec3A) -> Vec3A {
+        tetrahedron_closest_face(self, p).0
+    }
+    fn bounds(&self) -> Option<Aabb3d> {
+        Some(Aabb3d::from_point_cloud(
+            Isometry3d::IDENTITY,
+            self.vertices.into_iter(),
+        ))
+    }
+    fn ray_intersection(&self, ray: Ray3d) -> Option<RayIntersection> {
+        let origin = Vec3A::from(ray.origin);
+        let direction = Vec3A::from(ray.direction.as_vec3());
+        let hit = tetrahedron_faces(self).into_iter().try_fold(
+            (f32::NEG_INFINITY, f32::INFINITY),
+            |interval, ([a, _, _], face_normal)| {
+                let height = (origin - a).dot(face_normal);
+                let along = direction.dot(face_normal);
+                overlap(
+                    interval,
+                    slab_interval(height, along, f32::NEG_INFINITY, 0.0)?,
+                )
+            },
+        );
+        Some(RayIntersection::new(hit, convex_deepest_point(self, ray)))
+    }
+}
+
+/// the corners and outward normal of every face
+fn tetrahedron_faces(tetrahedron: &Tetrahedron) -> [([Vec3A; 3], Vec3A); 4] {
+    let vertices = tetrahedron.vertices.map(Vec3A::from);
+    [0, 1, 2, 3].map(|opposite| {
+        let corners = [1, 2, 3].map(|i| vertices[(opposite + i) % 4]);
+        let [a, b, c] = corners;
+        let face_normal = (b - a).cross(c - a).normalize_or(Vec3A::Y);
+        if face_normal.dot(vertices[opposite] - a) > 0.0 {
+            (corners, -face_normal)
+        } else {
+            (corners, face_normal)
+        }
+    })
+}
+
+/// the closest point on the surface, the outward normal of the face it's on and whether `p` is
+/// inside of the tetrahedron
+fn tetrahedron_closest_face(tetrahedron: &Tetrahedron, p: Vec3A) -> (Vec3A, Vec3A, bool) {
+    let mut inside = true;
+    let mut closest = (Vec3A::ZERO, Vec3A::Y, f32::INFINITY);
+    for ([a, b, c], face_normal) in tetrahedron_faces(tetrahedron) {
+        if (p - a).dot(face_normal) > 0.0 {
+            inside = false;
+        }
+        let point = closest_point_on_triangle(p, a, b, c);
+        let distance = p.distance_squared(point);
+        if distance < closest.2 {
+            closest = (point, face_normal, distance);
+        }
+    }
+    (closest.0, closest.1, inside)
+}
+
+/// from Real-Time Collision Detection by Christer Ericson, section 5.1.5
+pub(crate) fn closest_point_on_triangle(p: Vec3A, a: Vec3A, b: Vec3A, c: Vec3A) -> Vec3A {
+    let ab = b - a;
+    let ac = c - a;
+    let ap = p - a;
+    let d1 = ab.dot(ap);
+    let d2 = ac.dot(ap);
+    if d1 <= 0.0 && d2 <= 0.0 {
+        return a;
+    }
+    let bp = p - b;
+    let d3 = ab.dot(bp);
+    let d4 = ac.dot(bp);
+    if d3 >= 0.0 && d4 <= d3 {
+        return b;
+    }
+    let vc = d1 * d4 - d3 * d2;
+    if vc <= 0.0 && d1 >= 0.0 && d3 <= 0.0 {
+        return a + ab * (d1 / (d1 - d3));
+    }
+    let cp = p - c;
+    let d5 = ab.dot(cp);
+    let d6 = ac.dot(cp);
+    if d6 >= 0.0 && d5 <= d6 {
+        return c;
+    }
+    let vb = d5 * d2 - d1 * d6;
+    if vb <= 0.0 && d2 >= 0.0 && d6 <= 0.0 {
+        return a + ac * (d2 / (d2 - d6));
+    }
+    let va = d3 * d6 - d5 * d4;
+    if va <= 0.0 && (d4 - d3) >= 0.0 && (d5 - d6) >= 0.0 {
+        return b + (c - b) * ((d4 - d3) / ((d4 - d3) + (d5 - d6)));
+    }
+    let denom = 1.0 / (va + vb + vc);
+    a + ab * (vb * denom) + ac * (vc * denom)
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::math::bounding::BoundingVolume;
+
+    use super::*;
+
+    fn primitives() -> Vec<(&'static str, Box<dyn SignedDistance>)> {
+        vec![
+            ("sphere", Box::new(Sphere::new(0.5))),
+            ("cuboid", Box::new(Cuboid::new(1.0, 0.6, 0.4))),
+            ("torus", Box::new(Torus::new(0.2, 0.6))),
+            ("cylinder", Box::new(Cylinder::new(0.4, 1.0))),
+            (
+                "rounded cuboid",
+                Box::new(RoundedCuboid::new(1.0, 0.6, 0.4, 0.1)),
+            ),
+            (
+                "panel",
+                Box::new(Panel::new(1.0, 0.6).with_corner_radius(0.1)),
+            ),
+            (
+                "curved panel",
+                Box::new(CurvedPanel::new(0.8, 2.0, 0.5, 0.1)),
+            ),
+            (
+                "wide curved panel",
+                Box::new(CurvedPanel::new(0.8, 5.0, 0.5, 0.1)),
+            ),
+            ("capsule", Box::new(Capsule3d::new(0.3, 0.8))),
+            ("cone", Box::new(Cone::new(0.4, 1.0))),
+            (
+                "frustum",
+                Box::new(ConicalFrustum {
+                    radius_top: 0.2,
+                    radius_bottom: 0.5,
+                    height: 0.8,
+                }),
+            ),
+            ("tetrahedron", Box::new(Tetrahedron::default())),
+            ("triangle", Box::new(Triangle3d::default())),
+            (
+                "plane",
+                Box::new(Plane3d::new(Vec3::new(1.0, 1.0, 0.0), Vec2::new(0.5, 0.3))),
+            ),
+        ]
+    }
+
+    /// evenly spread over the cube from `-half_size` to `half_size`, without needing a random
+    /// number generator
+    fn points(half_size: f32) -> impl Iterator<Item = Vec3A> {
+        (1..400).map(move |i| {
+            let v = vec3a(0.618_034, 0.754_878, 0.569_84) * i as f32;
+            ((v - v.floor()) * 2.0 - 1.0) * half_size
+        })
+    }
+
+    #[test]
+    fn ray_intersections_lie_on_the_surface() {
+        for (name, sdf) in primitives() {
+            let half_size = sdf.bounds().unwrap().half_size().max_element();
+            let targets = points(half_size).collect::<Vec<_>>();
+            let origins = points(half_size * 3.0).zip(targets.iter().rev());
+            for (origin, target) in origins {
+                let Ok(direction) = Dir3::new((*target - origin).into()) else {
+                    continue;
+                };
+                let ray = Ray3d::new(origin.into(), direction);
+                let intersection = sdf.ray_intersection(ray).unwrap();
+                let distance = |t: f32| sdf.distance(ray.get_point(t).into());
+                // the first point clearly inside of the shape when walking along the ray
+                let inside = (0..4000)
+                    .map(|i| i as f32 * half_size * 0.002)
+                    .find(|t| distance(*t) < -1e-3);
+                match (intersection.hit, inside) {
+                    (Some((entry, exit)), _) => {
+                        if entry >= 0.0 {
+                            assert!(distance(entry).abs() < 1e-4, "{name} entry of {ray:?}");
+                        }
+                        assert!(distance(exit).abs() < 1e-4, "{name} exit of {ray:?}");
+                        assert!(inside.is_none_or(|t| entry <= t), "{name} skips {ray:?}");
+                    }
+                    (None, Some(_)) => panic!("{name} misses {ray:?}"),
+                    (None, None) => {}
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn normals_match_the_distance_gradient() {
+        for (name, sdf) in primitives() {
+            let half_size = sdf.bounds().unwrap().half_size().max_element();
+            for p in points(half_size * 1.5) {
+                if sdf.distance(p).abs() < 1e-3 {
+                    continue;
+                }
+                let expected = finite_difference_normal(|p| sdf.distance(p), p);
+                let normal = sdf.normal(p);
+                assert!(
+                    normal.dot(expected) > 0.999,
+                    "{name} at {p}: {normal} instead of {expected}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn closest_points_lie_on_the_surface() {
+        for (name, sdf) in primitives() {
+            let half_size = sdf.bounds().unwrap().half_size().max_element();
+            for p in points(half_size * 1.5) {
+                let closest = sdf.closest_point(p);
+                assert!(sdf.distance(closest).abs() < 1e-4, "{name} at {p}");
+                assert!(
+                    (p.distance(closest) - sdf.distance(p).abs()).abs() < 1e-4,
+                    "{name} at {p}: {closest} isn't the closest point"
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,248 @@
+use bevy::{
+    math::{
+        Vec3A,
+        bounding::{Aabb3d, BoundingVolume},
+    },
+    prelude::*,
+};
+
+use super::SignedDistance;
+
+/// The exact intersection of a ray with a shape, in the local space of the shape
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RayIntersection {
+    /// the distances along the ray where it enters and leaves the shape, the entry is negative when
+    /// the ray starts inside of it
+    pub hit: Option<(f32, f32)>,
+    /// the distance along the ray where the shape is closest, or where the ray is deepest inside of
+    /// it for a hit, never negative
+    pub closest: f32,
+}
+
+impl RayIntersection {
+    /// `line_hit` and `deepest` are measured along the whole line the ray lies on. For shapes that
+    /// aren't convex `line_hit` has to be the first part of the line inside of the shape that
+    /// doesn't end behind the ray.
+    pub fn new(line_hit: Option<(f32, f32)>, deepest: f32) -> Self {
+        Self {
+            hit: line_hit.filter(|(_, exit)| *exit >= 0.0),
+            closest: deepest.max(0.0),
+        }
+    }
+    /// intersects a convex shape using only its distance, exact up to float precision. `None` when
+    /// the shape has no bounds.
+    pub fn convex(sdf: &(impl SignedDistance + ?Sized), ray: Ray3d) -> Option<Self> {
+        let (start, end) = approach_interval(sdf.bounds()?, ray);
+        let distance = |t: f32| sdf.distance(ray.get_point(t).into());
+        let deepest = golden_section_min(distance, start, end);
+        let hit = (distance(deepest) <= 0.0).then(|| {
+            (
+                find_surface(distance, start, deepest),
+                find_surface(distance, end, deepest),
+            )
+        });
+        Some(Self::new(hit, deepest))
+    }
+}
+
+/// where along the line the distance of a convex shape is the lowest, `0.0` for unbounded shapes
+pub(crate) fn convex_deepest_point(sdf: &(impl SignedDistance + ?Sized), ray: Ray3d) -> f32 {
+    let Some(bounds) = sdf.bounds() else {
+        return 0.0;
+    };
+    let (start, end) = approach_interval(bounds, ray);
+    golden_section_min(|t| sdf.distance(ray.get_point(t).into()), start, end)
+}
+
+/// like [`convex_deepest_point`] for shapes that aren't convex, but only in front of the ray as the
+/// lowest distance behind it says nothing about the distance in front of it. Every section of the
+/// line between `splits` gets searched on its own, which finds the lowest distance as long as no
+/// section has more than one local minimum.
+pub(crate) fn sectioned_deepest_point(
+    sdf: &(impl SignedDistance + ?Sized),
+    ray: Ray3d,
+    splits: impl IntoIterator<Item = f32>,
+) -> f32 {
+    let Some(bounds) = sdf.bounds() else {
+        return 0.0;
+    };
+    let (start, end) = approach_interval(bounds, ray);
+    let (start, end) = (start.max(0.0), end.max(0.0));
+    let distance = |t: f32| sdf.distance(ray.get_point(t).into());
+    let mut points = splits
+        .into_iter()
+        .filter(|t| *t > start && *t < end)
+        .chain([start, end])
+        .collect::<Vec<_>>();
+    points.sort_by(f32::total_cmp);
+    points
+        .windows(2)
+        .map(|section| golden_section_min(distance, section[0], section[1]))
+        .min_by(|a, b| distance(*a).total_cmp(&distance(*b)))
+        .unwrap_or(start)
+}
+
+/// the part of the line that has to contain the lowest distance of a convex shape inside of
+/// `bounds`, both ends are outside of the shape
+fn approach_interval(bounds: Aabb3d, ray: Ray3d) -> (f32, f32) {
+    let radius = bounds.half_size().length();
+    let to_center = bounds.center() - Vec3A::from(ray.origin);
+    let direction = Vec3A::from(ray.direction.as_vec3());
+    let along = to_center.dot(direction);
+    let offset = (to_center - direction * along).length();
+    // the shape is at most `offset + radius` away at `along`, further out the bounding sphere alone
+    // is further away than that
+    let half_width = 2.0 * (radius * (radius + offset)).sqrt() + f32::EPSILON;
+    (along - half_width, along + half_width)
+}
+
+/// only works for convex functions
+pub(crate) fn golden_section_min(f: impl Fn(f32) -> f32, mut start: f32, mut end: f32) -> f32 {
+    const ITERATIONS: usize = 40;
+    let ratio = (5f32.sqrt() - 1.0) / 2.0;
+    let mut a = end - (end - start) * ratio;
+    let mut b = start + (end - start) * ratio;
+    let (mut fa, mut fb) = (f(a), f(b));
+    for _ in 0..ITERATIONS {
+        if fa < fb {
+            end = b;
+            (b, fb) = (a, fa);
+            a = end - (end - start) * ratio;
+            fa = f(a);
+        } else {
+            start = a;
+            (a, fa) = (b, fb);
+            b = start + (end - start) * ratio;
+            fb = f(b);
+        }
+    }
+    (start + end) / 2.0
+}
+
+/// bisects between a point outside of the shape and one inside of it
+fn find_surface(f: impl Fn(f32) -> f32, mut outside: f32, mut inside: f32) -> f32 {
+    const ITERATIONS: usize = 32;
+    for _ in 0..ITERATIONS {
+        let middle = (outside + inside) / 2.0;
+        if f(middle) <= 0.0 {
+            inside = middle;
+        } else {
+            outside = middle;
+        }
+    }
+    (outside + inside) / 2.0
+}
+
+/// where the line enters and leaves the box, `None` if it misses it
+pub(crate) fn box_interval(ray: Ray3d, min: Vec3A, max: Vec3A) -> Option<(f32, f32)> {
+    let origin = Vec3A::from(ray.origin);
+    let direction = Vec3A::from(ray.direction.as_vec3());
+    (0..3)
+        .map(|axis| slab_interval(origin[axis], direction[axis], min[axis], max[axis]))
+        .try_fold((f32::NEG_INFINITY, f32::INFINITY), |interval, slab| {
+            overlap(interval, slab?)
+        })
+}
+
+/// where a line along a single axis is between `min` and `max`
+pub(crate) fn slab_interval(origin: f32, direction: f32, min: f32, max: f32) -> Option<(f32, f32)> {
+    if direction == 0.0 {
+        return (min..=max)
+            .contains(&origin)
+            .then_some((f32::NEG_INFINITY, f32::INFINITY));
+    }
+    let t1 = (min - origin) / direction;
+    let t2 = (max - origin) / direction;
+    Some((t1.min(t2), t1.max(t2)))
+}
+
+pub(crate) fn overlap(a: (f32, f32), b: (f32, f32)) -> Option<(f32, f32)> {
+    let interval = (a.0.max(b.0), a.1.min(b.1));
+    (interval.0 <= interval.1).then_some(interval)
+}
+
+/// where the line enters and leaves the sphere
+pub(crate) fn sphere_interval(ray: Ray3d, center: Vec3A, radius: f32) -> Option<(f32, f32)> {
+    let to_center = center - Vec3A::from(ray.origin);
+    let direction = Vec3A::from(ray.direction.as_vec3());
+    let along = to_center.dot(direction);
+    let half_chord_squared = radius * radius - (to_center - direction * along).length_squared();
+    (half_chord_squared >= 0.0).then(|| {
+        let half_chord = half_chord_squared.sqrt();
+        (along - half_chord, along + half_chord)
+    })
+}
+
+/// the real roots of `x⁴ + a x³ + b x² + c x + d` in ascending order, with Ferrari's method
+pub(crate) fn quartic_roots([a, b, c, d]: [f64; 4]) -> Vec<f64> {
+    // depressed to `y⁴ + p y² + q y + r` with `x = y - a / 4`
+    let shift = a / 4.0;
+    let p = b - 6.0 * shift * shift;
+    let q = c - 2.0 * b * shift + 8.0 * shift.powi(3);
+    let r = d - c * shift + b * shift * shift - 3.0 * shift.powi(4);
+    // the roots of `y² + linear y + constant`
+    let quadratic = |linear: f64, constant: f64| {
+        let discriminant = linear * linear / 4.0 - constant;
+        (discriminant >= 0.0)
+            .then(|| {
+                let root = discriminant.sqrt();
+                [-linear / 2.0 - root, -linear / 2.0 + root]
+            })
+            .into_iter()
+            .flatten()
+    };
+    // splits it into two quadratics, `m` has to be positive for that
+    let m = largest_cubic_root([p, p * p / 4.0 - r, -q * q / 8.0]);
+    let mut roots = if q.abs() < 1e-12 || m <= 0.0 {
+        // biquadratic, a quadratic in `y²`
+        quadratic(p, r)
+            .filter(|y_squared| *y_squared >= 0.0)
+            .flat_map(|y_squared| [-y_squared.sqrt(), y_squared.sqrt()])
+            .collect::<Vec<_>>()
+    } else {
+        let s = (2.0 * m).sqrt();
+        let offset = q / (2.0 * s);
+        quadratic(s, p / 2.0 + m - offset)
+            .chain(quadratic(-s, p / 2.0 + m + offset))
+            .collect()
+    };
+    let value = |x: f64| (((x + a) * x + b) * x + c) * x + d;
+    for root in &mut roots {
+        *root -= shift;
+        // polishes away the cancellation of the closed form, steps that make it worse are skipped
+        // as they happen close to double roots
+        for _ in 0..2 {
+            let x = *root;
+            let slope = ((4.0 * x + 3.0 * a) * x + 2.0 * b) * x + c;
+            let polished = x - value(x) / slope;
+            if value(polished).abs() < value(x).abs() {
+                *root = polished;
+            }
+        }
+    }
+    roots.sort_by(f64::total_cmp);
+    roots
+}
+
+/// the largest real root of `x³ + a x² + b x + c`, with Cardano's method
+fn largest_cubic_root([a, b, c]: [f64; 3]) -> f64 {
+    // depressed to `t³ + p t + q` with `x = t - a / 3`
+    let shift = a / 3.0;
+    let p = b - a * a / 3.0;
+    let q = 2.0 * a.powi(3) / 27.0 - a * b / 3.0 + c;
+    let discriminant = q * q / 4.0 + p.powi(3) / 27.0;
+    let t = if discriminant > 0.0 {
+        let root = discriminant.sqrt();
+        (-q / 2.0 + root).cbrt() + (-q / 2.0 - root).cbrt()
+    } else if p < 0.0 {
+        // three real roots
+        let angle = (3.0 * q / (2.0 * p) * (-3.0 / p).sqrt())
+            .clamp(-1.0, 1.0)
+            .acos()
+            / 3.0;
+        2.0 * (-p / 3.0).sqrt() * angle.cos()
+    } else {
+        0.0
+    };
+    t - shift
+}
@@ -6,7 +6,7 @@ use bevy::{
 #[cfg(feature = "xr")]
 use bevy_mod_xr::hands::HandBone;
 
-use crate::field::Field;
+use crate::field::{Field, FieldTransform};
 
 #[derive(Clone, Copy, Debug, Reflect)]
 pub struct Joint {
@@ -164,7 +164,7 @@ impl Hand {
 }
 
 impl Hand {
-    pub fn distance(&self, field: &Field, field_transform: &GlobalTransform) -> f32 {
+    pub fn tips(&self) -> [Joint; 5] {
         [
             self.thumb.tip,
             self.index.tip,
@@ -172,48 +172,39 @@ impl Hand {
             self.ring.tip,
             self.little.tip,
         ]
-        .map(|tip| field.distance(field_transform, tip.pos))
-        .into_iter()
-        .reduce(f32::min)
-        .unwrap()
     }
-    pub fn closest_point(&self, field: &Field, field_transform: &GlobalTransform) -> Vec3A {
-        [
-            self.thumb.tip,
-            self.index.tip,
-            self.middle.tip,
-            self.ring.tip,
-            self.little.tip,
-        ]
-        .map(|tip| {
-            (
-                field.distance(field_transform, tip.pos),
-                field.closest_point(field_transform, tip.pos),
-            )
-        })
-        .into_iter()
-        .reduce(|(v1, p1), (v2, p2)| if v1 < v2 { (v1, p1) } else { (v2, p2) })
-        .map(|(_, p)| p)
-        .unwrap()
+    pub fn distance(&self, field: &Field, field_transform: &impl FieldTransform) -> f32 {
+        self.tips()
+            .map(|tip| field.distance(field_transform, tip.pos))
+            .into_iter()
+            .reduce(f32::min)
+            .unwrap()
     }
-    pub fn normal(&self, field: &Field, field_transform: &GlobalTransform) -> Dir3A {
-        [
-            self.thumb.tip,
-            self.index.tip,
-            self.middle.tip,
-            self.ring.tip,
-            self.little.tip,
-        ]
-        .map(|tip| {
-            (
-                field.distance(field_transform, tip.pos),
-                field.normal(field_transform, tip.pos),
-            )
-        })
-        .into_iter()
-        .reduce(|(v1, p1), (v2, p2)| if v1 < v2 { (v1, p1) } else { (v2, p2) })
-        .map(|(_, p)| p)
-        .unwrap()
+    pub fn closest_point(&self, field: &Field, field_transform: &impl FieldTransform) -> Vec3A {
+        self.tips()
+            .map(|tip| {
+                (
+                    field.distance(field_transform, tip.pos),
+                    field.closest_point(field_transform, tip.pos),
+                )
+            })
+            .into_iter()
+            .reduce(|(v1, p1), (v2, p2)| if v1 < v2 { (v1, p1) } else { (v2, p2) })
+            .map(|(_, p)| p)
+            .unwrap()
+    }
+    pub fn normal(&self, field: &Field, field_transform: &impl FieldTransform) -> Dir3A {
+        self.tips()
+            .map(|tip| {
+                (
+                    field.distance(field_transform, tip.pos),
+                    field.normal(field_transform, tip.pos),
+                )
+            })
+            .into_iter()
+            .reduce(|(v1, p1), (v2, p2)| if v1 < v2 { (v1, p1) } else { (v2, p2) })
+            .map(|(_, p)| p)
+            .unwrap()
     }
 }
 
@@ -275,7 +266,8 @@ fn mul_joint(mat: &Mat4, joint: Joint) -> Joint {
     Joint {
         pos: mat.transform_point(joint.pos),
         rot: mat.to_scale_rotation_translation().1 * joint.rot,
-        radius: joint.radius,
+        // the average scale, exact for uniform scale
+        radius: joint.radius * mat.determinant().abs().cbrt(),
     }
 }
 
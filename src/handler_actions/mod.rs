@@ -1,10 +1,14 @@
-use bevy::ecs::entity::{Entity, EntityHashSet};
+use bevy::{
+    ecs::entity::{Entity, EntityHashSet, EntityMapper, MapEntities},
+    reflect::{Reflect, std_traits::ReflectDefault},
+};
 
 pub mod simple;
 pub mod multi;
 pub mod single;
 
-#[derive(Clone, Default, Debug)]
+#[derive(Clone, Default, Debug, Reflect)]
+#[reflect(Clone, Default, Debug)]
 pub struct DeltaEntitySet {
     added: EntityHashSet,
     current: EntityHashSet,
@@ -35,3 +39,11 @@ impl DeltaEntitySet {
         &self.removed
     }
 }
+
+impl MapEntities for DeltaEntitySet {
+    fn map_entities<E: EntityMapper>(&mut self, entity_mapper: &mut E) {
+        self.added.map_entities(entity_mapper);
+        self.current.map_entities(entity_mapper);
+        self.removed.map_entities(entity_mapper);
+    }
+}
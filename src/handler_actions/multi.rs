@@ -1,15 +1,32 @@
-use bevy::ecs::component::Component;
+use bevy::{
+    ecs::{
+        component::Component,
+        entity::{EntityMapper, MapEntities},
+    },
+    prelude::ReflectComponent,
+    reflect::{Reflect, std_traits::ReflectDefault},
+};
 
 use crate::{input_handler::InputHandler, input_method_data::InputData};
 
 use super::{DeltaEntitySet, simple::SimpleHandlerAction};
 
-#[derive(Component, Default, Debug)]
+#[derive(Component, Default, Debug, Reflect)]
+#[reflect(Component, Default, Debug)]
 pub struct MultiHandlerAction {
+    #[entities]
     simple: SimpleHandlerAction,
+    #[entities]
     hovering: DeltaEntitySet,
 }
 
+impl MapEntities for MultiHandlerAction {
+    fn map_entities<E: EntityMapper>(&mut self, entity_mapper: &mut E) {
+        self.simple.map_entities(entity_mapper);
+        self.hovering.map_entities(entity_mapper);
+    }
+}
+
 impl MultiHandlerAction {
     pub fn update(
         &mut self,
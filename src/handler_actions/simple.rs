@@ -1,15 +1,32 @@
-use bevy::ecs::{component::Component, entity::Entity};
+use bevy::{
+    ecs::{
+        component::Component,
+        entity::{Entity, EntityMapper, MapEntities},
+    },
+    prelude::ReflectComponent,
+    reflect::{Reflect, std_traits::ReflectDefault},
+};
 
 use crate::{input_handler::InputHandler, input_method_data::InputData};
 
 use super::DeltaEntitySet;
 
-#[derive(Component, Default, Debug)]
+#[derive(Component, Default, Debug, Reflect)]
+#[reflect(Component, Default, Debug)]
 pub struct SimpleHandlerAction {
+    #[entities]
     actors: DeltaEntitySet,
+    #[entities]
     wanted_actors: DeltaEntitySet,
 }
 
+impl MapEntities for SimpleHandlerAction {
+    fn map_entities<E: EntityMapper>(&mut self, entity_mapper: &mut E) {
+        self.actors.map_entities(entity_mapper);
+        self.wanted_actors.map_entities(entity_mapper);
+    }
+}
+
 impl SimpleHandlerAction {
     pub fn update(
         &mut self,
@@ -1,15 +1,22 @@
-use bevy::ecs::{component::Component, entity::Entity};
+use bevy::{
+    ecs::{component::Component, entity::Entity},
+    prelude::ReflectComponent,
+    reflect::{Reflect, std_traits::ReflectDefault},
+};
 
 use crate::{input_handler::InputHandler, input_method_data::InputData};
 
 use super::{DeltaEntitySet, multi::MultiHandlerAction};
 
-#[derive(Component, Default, Debug)]
+#[derive(Component, Default, Debug, Reflect)]
+#[reflect(Component, Default, Debug)]
 pub struct SingleHandlerAction {
+    #[entities]
     multi: MultiHandlerAction,
     actor_started: bool,
     actor_changed: bool,
     actor_stopped: bool,
+    #[entities]
     actor: Option<Entity>,
 }
 
@@ -1,19 +1,69 @@
-use bevy::prelude::*;
+use bevy::{
+    ecs::entity::{EntityMapper, MapEntities},
+    prelude::*,
+};
 
-use crate::{input_method_capturing::InputMethodMessage, input_method_data::InputData};
+use crate::{
+    input_layers::InputLayers, input_method_capturing::InputMethodMessage,
+    input_method_data::InputData,
+};
 
-#[derive(Component, Debug)]
-#[require(Transform)]
+#[derive(Component, Debug, Reflect)]
+#[reflect(Component, Debug)]
+#[require(Transform, InputLayers)]
 pub struct InputHandler {
+    #[reflect(ignore)]
     input_events: Vec<InputData>,
+    #[reflect(ignore)]
     messages: Vec<(Entity, InputMethodMessage)>,
+    #[entities]
     field_ref: FieldRef,
 }
 
-#[derive(Clone, Copy, Debug)]
+/// Which [`Field`](crate::field::Field)s an [`InputHandler`] uses, with multiple fields the
+/// closest one counts
+#[derive(Clone, Debug, Reflect)]
+#[reflect(Debug, Clone)]
 pub enum FieldRef {
     This,
     Entity(Entity),
+    Entities(Vec<Entity>),
+    /// every field on the handler and all of its descendants
+    Descendants,
+}
+
+impl FieldRef {
+    /// the entities the fields of `handler` should be on, [`FieldRef::Descendants`] includes
+    /// entities without a field
+    pub fn entities<'a>(
+        &'a self,
+        handler: Entity,
+        children: &'a Query<&Children>,
+    ) -> impl Iterator<Item = Entity> + 'a {
+        let this = matches!(self, FieldRef::This | FieldRef::Descendants).then_some(handler);
+        let listed = match self {
+            FieldRef::Entity(entity) => std::slice::from_ref(entity),
+            FieldRef::Entities(entities) => entities.as_slice(),
+            FieldRef::This | FieldRef::Descendants => &[],
+        };
+        let descendants = matches!(self, FieldRef::Descendants)
+            .then(|| children.iter_descendants(handler))
+            .into_iter()
+            .flatten();
+        this.into_iter()
+            .chain(listed.iter().copied())
+            .chain(descendants)
+    }
+}
+
+impl MapEntities for FieldRef {
+    fn map_entities<E: EntityMapper>(&mut self, entity_mapper: &mut E) {
+        match self {
+            FieldRef::Entity(entity) => *entity = entity_mapper.get_mapped(*entity),
+            FieldRef::Entities(entities) => entities.map_entities(entity_mapper),
+            FieldRef::This | FieldRef::Descendants => {}
+        }
+    }
 }
 
 impl InputHandler {
@@ -35,10 +85,10 @@ impl InputHandler {
     pub fn release(&mut self, method: Entity) {
         self.messages.push((method, InputMethodMessage::Release));
     }
-    pub const fn get_field_ref(&self) -> FieldRef {
-        self.field_ref
+    pub const fn get_field_ref(&self) -> &FieldRef {
+        &self.field_ref
     }
-    pub const fn set_field_ref(&mut self, field_ref: FieldRef) {
+    pub fn set_field_ref(&mut self, field_ref: FieldRef) {
         self.field_ref = field_ref;
     }
 }
@@ -0,0 +1,56 @@
+use bevy::prelude::*;
+
+/// Which layers an [`InputMethod`](crate::input_method::InputMethod) or
+/// [`InputHandler`](crate::input_handler::InputHandler) is on, methods only reach handlers they
+/// share at least one layer with. Both are on layer 0 by default.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Hash, Reflect)]
+#[reflect(Component, Debug, Default, PartialEq, Hash, Clone)]
+pub struct InputLayers(u32);
+
+impl InputLayers {
+    pub const DEFAULT: InputLayers = InputLayers::layer(0);
+    pub const ALL: InputLayers = InputLayers(u32::MAX);
+    pub const NONE: InputLayers = InputLayers(0);
+    /// the highest layer that can be used
+    pub const MAX_LAYER: u32 = u32::BITS - 1;
+
+    /// only on `layer`, panics for layers above [`InputLayers::MAX_LAYER`]
+    pub const fn layer(layer: u32) -> InputLayers {
+        InputLayers::NONE.with(layer)
+    }
+    /// panics for layers above [`InputLayers::MAX_LAYER`]
+    pub const fn with(self, layer: u32) -> InputLayers {
+        assert!(layer <= Self::MAX_LAYER, "input layer out of range");
+        InputLayers(self.0 | 1 << layer)
+    }
+    /// panics for layers above [`InputLayers::MAX_LAYER`]
+    pub const fn without(self, layer: u32) -> InputLayers {
+        assert!(layer <= Self::MAX_LAYER, "input layer out of range");
+        InputLayers(self.0 & !(1 << layer))
+    }
+    pub const fn contains(&self, layer: u32) -> bool {
+        layer <= Self::MAX_LAYER && self.0 & 1 << layer != 0
+    }
+    /// whether both share at least one layer
+    pub const fn intersects(&self, other: &InputLayers) -> bool {
+        self.0 & other.0 != 0
+    }
+    pub const fn bits(&self) -> u32 {
+        self.0
+    }
+    pub const fn from_bits(bits: u32) -> InputLayers {
+        InputLayers(bits)
+    }
+}
+
+impl Default for InputLayers {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+impl FromIterator<u32> for InputLayers {
+    fn from_iter<T: IntoIterator<Item = u32>>(iter: T) -> Self {
+        iter.into_iter().fold(InputLayers::NONE, InputLayers::with)
+    }
+}
@@ -1,12 +1,21 @@
 use bevy::prelude::*;
 
-use crate::input_method_data::{NonSpatialInputData, SpatialInputData};
+use crate::{
+    input_layers::InputLayers,
+    input_method_data::{NonSpatialInputData, SpatialInputData},
+    order_helper::HandlerDistance,
+};
 
-#[derive(Component, Debug, Default)]
-#[require(SpatialInputData, NonSpatialInputData)]
+#[derive(Component, Debug, Default, Reflect)]
+#[reflect(Component, Debug, Default)]
+#[require(SpatialInputData, NonSpatialInputData, InputLayers)]
 pub struct InputMethod {
+    #[entities]
     captured_by: Option<Entity>,
+    #[entities]
     handler_order: Vec<Entity>,
+    #[reflect(ignore)]
+    handler_distances: Vec<HandlerDistance>,
 }
 
 impl InputMethod {
@@ -14,8 +23,11 @@ impl InputMethod {
         InputMethod {
             captured_by: None,
             handler_order: Vec::new(),
+            handler_distances: Vec::new(),
         }
     }
+    /// gets overwritten every frame unless the method has
+    /// [`HandlerOrdering::Manual`](crate::order_helper::HandlerOrdering::Manual)
     pub fn set_handler_order(&mut self, order: Vec<Entity>) {
         self.handler_order = order;
     }
@@ -34,4 +46,12 @@ impl InputMethod {
     pub(crate) fn get_handler_order(&self) -> &Vec<Entity> {
         &self.handler_order
     }
+    /// every handler in range, also the ones left out of the handler order because they're
+    /// occluded, the input data only gets sent to these
+    pub(crate) fn get_handler_distances(&self) -> &[HandlerDistance] {
+        &self.handler_distances
+    }
+    pub(crate) fn set_handler_distances(&mut self, distances: Vec<HandlerDistance>) {
+        self.handler_distances = distances;
+    }
 }
@@ -6,10 +6,15 @@ use bevy::{
 
 use crate::{
     SuisPreUpdateSets,
-    field::Field,
+    field::{Field, FieldCache},
     input_handler::{FieldRef, InputHandler},
+    input_layers::InputLayers,
     input_method::InputMethod,
     input_method_data::{InputData, NonSpatialInputData, SpatialInputData},
+    order_helper::{HandlerDistance, closest_field_with_snap},
+    ray_occlusion::{RayOcclusion, is_occluded, occluder_ray_length},
+    raymarching::SuisRaymarchSettings,
+    target_assist::{Magnetism, TargetAssist},
 };
 pub struct InputMethodCapturingPlugin;
 
@@ -41,86 +46,131 @@ fn send_input_data(
         &InputMethod,
         &NonSpatialInputData,
         &SpatialInputData,
+        &InputLayers,
+        Option<&RayOcclusion>,
+        Option<&TargetAssist>,
     )>,
-    mut handlers: Query<(Entity, &GlobalTransform, &mut InputHandler)>,
-    field_query: Query<(&Field, &GlobalTransform)>,
+    mut handlers: Query<(
+        Entity,
+        &GlobalTransform,
+        &mut InputHandler,
+        &InputLayers,
+        Option<&Magnetism>,
+    )>,
+    field_query: Query<(&Field, &FieldCache, Option<&SuisRaymarchSettings>)>,
+    children: Query<&Children>,
+    raymarch_settings: Res<SuisRaymarchSettings>,
 ) {
     let mut handler_data = EntityHashMap::<Vec<InputData>>::default();
-    for (input_method, method, data, input) in &methods {
+    for (input_method, method, data, input, layers, occlusion, assist) in &methods {
+        let input_data = |handler_distance: &HandlerDistance, captured| {
+            let (_, handler_transform, ..) = handlers.get(handler_distance.handler).ok()?;
+            let (field, field_transform, settings) =
+                field_query.get(handler_distance.field).ok()?;
+            let settings = settings.unwrap_or(&raymarch_settings);
+            let global_to_handler = handler_transform.compute_matrix().inverse();
+            let distance = handler_distance.distance;
+            Some(InputData {
+                input_method,
+                spatial_data: input.transform(&global_to_handler),
+                non_spatial_data: *data,
+                handler_location: *handler_transform,
+                field: handler_distance.field,
+                distance: distance.distance,
+                ray_length: distance.ray_length(),
+                ray_hit: distance.hit,
+                cast_offset: distance.cast_offset,
+                snap_point: handler_distance.snap.map(|snap| snap.point),
+                // snapped inputs are where they snapped to
+                surface_coordinates: match handler_distance.snap {
+                    Some(snap) => field.surface_coordinates(field_transform, snap.point),
+                    None => input.surface_coordinates(field, field_transform, settings),
+                },
+                occluded: false,
+                captured,
+            })
+        };
         if let Some(handler) = method.captured_by() {
-            let Ok((handler, handler_transform, input_handler)) = handlers
+            let Ok((handler, _, input_handler, handler_layers, magnetism)) = handlers
                 .get(handler)
                 .inspect_err(|err| error!("Invalid InputHandler Capturing InputMethod: {err}"))
             else {
                 continue;
             };
-            let Some(data) = get_data_for_handler(
-                handler,
-                handler_transform,
-                input_handler,
-                field_query,
-                |global_to_handler, field, field_transform| InputData {
-                    input_method,
-                    spatial_data: input.transform(&global_to_handler),
-                    non_spatial_data: *data,
-                    handler_location: *handler_transform,
-                    distance: input.distance(field, field_transform),
-                    captured: true,
-                },
-            ) else {
+            if !handler_layers.intersects(layers) {
+                continue;
+            }
+            // the captured handler gets its data no matter how far away it is
+            let handler_distance = method
+                .get_handler_distances()
+                .iter()
+                .find(|handler_distance| handler_distance.handler == handler)
+                .copied()
+                .or_else(|| {
+                    let field_ref = input_handler.get_field_ref();
+                    let fields = || {
+                        field_ref.entities(handler, &children).filter_map(|entity| {
+                            let (field, field_transform, settings) = field_query
+                                .get(entity)
+                                .inspect_err(|err| {
+                                    // descendants without a field are expected
+                                    if !matches!(field_ref, FieldRef::Descendants) {
+                                        error!("Invalid Field: {err}");
+                                    }
+                                })
+                                .ok()?;
+                            let settings = settings.unwrap_or(&raymarch_settings);
+                            Some((entity, field, field_transform, settings))
+                        })
+                    };
+                    let (field, distance, snap) =
+                        closest_field_with_snap(input, assist, magnetism, fields)?;
+                    Some(HandlerDistance {
+                        handler,
+                        field,
+                        distance,
+                        snap,
+                        priority: default(),
+                        transparent: false,
+                    })
+                });
+            let Some(data) = handler_distance.and_then(|distance| input_data(&distance, true))
+            else {
                 continue;
             };
             handler_data.entry(handler).or_default().push(data);
         } else {
-            for (handler, handler_transform, input_handler) in &handlers {
-                let Some(data) = get_data_for_handler(
-                    handler,
-                    handler_transform,
-                    input_handler,
-                    field_query,
-                    |global_to_handler, field, field_transform| InputData {
-                        input_method,
-                        spatial_data: input.transform(&global_to_handler),
-                        non_spatial_data: *data,
-                        handler_location: *handler_transform,
-                        distance: input.distance(field, field_transform),
-                        captured: false,
-                    },
-                ) else {
+            // only the handlers in range get data, with the distances from ordering them
+            let handler_distances = method.get_handler_distances();
+            let occluder_ray_length = occlusion.and_then(|_| {
+                occluder_ray_length(
+                    handler_distances
+                        .iter()
+                        .map(|handler| (handler.distance.ray_length(), handler.transparent)),
+                )
+            });
+            for handler_distance in handler_distances {
+                let Some(mut data) = input_data(handler_distance, false) else {
                     continue;
                 };
-                handler_data.entry(handler).or_default().push(data);
+                data.occluded = is_occluded(data.ray_length, occluder_ray_length);
+                if data.occluded && occlusion == Some(&RayOcclusion::Skip) {
+                    continue;
+                }
+                handler_data
+                    .entry(handler_distance.handler)
+                    .or_default()
+                    .push(data);
             }
         }
     }
 
-    for (handler, _, mut input_handler) in &mut handlers {
+    for (handler, _, mut input_handler, ..) in &mut handlers {
         let data = handler_data.remove(&handler).unwrap_or_default();
         input_handler.set_events(data);
     }
 }
 
-fn get_data_for_handler(
-    handler: Entity,
-    handler_transform: &GlobalTransform,
-    input_handler: &InputHandler,
-    field_query: Query<(&Field, &GlobalTransform)>,
-    creation_fn: impl FnOnce(Mat4, &Field, &GlobalTransform) -> InputData,
-) -> Option<InputData> {
-    let global_to_handler = handler_transform.compute_matrix().inverse();
-    let field_entity = match input_handler.get_field_ref() {
-        FieldRef::This => handler,
-        FieldRef::Entity(entity) => entity,
-    };
-    let Ok((field, field_transform)) = field_query
-        .get(field_entity)
-        .inspect_err(|err| error!("Invalid Field: {err}"))
-    else {
-        return None;
-    };
-    Some(creation_fn(global_to_handler, field, field_transform))
-}
-
 fn capture_input_methods(
     mut methods: Query<(&mut InputMethod, &InputMethodCaptureRequests)>,
     handlers: Query<Has<InputHandler>>,
@@ -136,8 +186,10 @@ fn capture_input_methods(
                 .is_ok_and(|v| v)
         });
         for handler in iter {
+            // the first handler in the order wins, which is the front-most one for rays
             if capture_requests.contains(&handler) {
                 method.set_captured(handler);
+                break;
             }
         }
     }
@@ -1,6 +1,17 @@
-use bevy::prelude::*;
+use std::cmp::Ordering;
 
-use crate::{field::Field, hand::Hand};
+use bevy::{
+    math::{Vec3A, bounding::Aabb3d},
+    prelude::*,
+};
+
+use crate::{
+    field::{Field, FieldTransform, SurfaceCoordinates},
+    hand::Hand,
+    parabolic_arc::ParabolicArc,
+    raymarching::{RayHit, RayMarchResult, SuisRaymarchSettings},
+    shape_cast::ShapeCast,
+};
 
 #[derive(Clone, Copy, Component, Debug, Reflect, Default)]
 pub struct NonSpatialInputData {
@@ -20,6 +31,15 @@ pub enum SpatialInputData {
     Hand(Hand),
     Tip(Isometry3d),
     Ray(Ray3d),
+    /// a ray that stops after `max_length`, for pointers with a limited range
+    FiniteRay {
+        ray: Ray3d,
+        max_length: f32,
+    },
+    /// a curved pointer for teleport and throw targeting, see [`ParabolicArc`]
+    Arc(ParabolicArc),
+    /// a ray with a sphere or cone around it for easier targeting, see [`ShapeCast`]
+    Cast(ShapeCast),
 }
 
 impl Default for SpatialInputData {
@@ -36,51 +56,272 @@ impl SpatialInputData {
                 rotation: mat.to_scale_rotation_translation().1 * isometry.rotation,
                 translation: mat.transform_point3a(isometry.translation),
             }),
-            SpatialInputData::Ray(ray) => SpatialInputData::Ray(Ray3d {
-                origin: mat.transform_point3(ray.origin),
-                direction: Dir3::new_unchecked(mat.transform_vector3(ray.direction.as_vec3())),
-            }),
+            SpatialInputData::Ray(ray) => SpatialInputData::Ray(transform_ray(ray, mat).0),
+            SpatialInputData::FiniteRay { ray, max_length } => {
+                let (ray, scale) = transform_ray(ray, mat);
+                SpatialInputData::FiniteRay {
+                    ray,
+                    max_length: max_length * scale,
+                }
+            }
+            SpatialInputData::Arc(arc) => SpatialInputData::Arc(arc.transform(mat)),
+            SpatialInputData::Cast(cast) => SpatialInputData::Cast(cast.transform(mat)),
+        }
+    }
+    /// how far along the ray input reaches, `f32::INFINITY` for anything but a
+    /// [`SpatialInputData::FiniteRay`], arcs have their own [`ParabolicArc::max_length`]
+    pub fn max_ray_length(&self) -> f32 {
+        match self {
+            SpatialInputData::FiniteRay { max_length, .. } => *max_length,
+            _ => f32::INFINITY,
         }
     }
-    pub fn distance(&self, field: &Field, field_transform: &GlobalTransform) -> f32 {
+    /// marches the ray, arc or cast against `field`, together with the world-space point where
+    /// it's closest to the field. A miss at the origin for inputs without a ray.
+    fn raymarch(
+        &self,
+        field: &Field,
+        field_transform: &impl FieldTransform,
+        settings: &SuisRaymarchSettings,
+    ) -> (RayMarchResult, Vec3) {
+        match self {
+            SpatialInputData::Ray(ray) | SpatialInputData::FiniteRay { ray, .. } => {
+                let settings = SuisRaymarchSettings {
+                    max_distance: settings.max_distance.min(self.max_ray_length().max(0.0)),
+                    ..*settings
+                };
+                let result = field.raymarch(field_transform, *ray, &settings);
+                (result, ray.get_point(result.deepest_point_ray_length))
+            }
+            SpatialInputData::Arc(arc) => {
+                let result = arc.raymarch(field, field_transform, settings);
+                (result, arc.point_at(result.deepest_point_ray_length))
+            }
+            SpatialInputData::Cast(cast) => {
+                let (result, _) = cast.raymarch(field, field_transform, settings);
+                (result, cast.ray.get_point(result.deepest_point_ray_length))
+            }
+            SpatialInputData::Hand(_) | SpatialInputData::Tip(_) => {
+                (RayMarchResult::MISS, Vec3::ZERO)
+            }
+        }
+    }
+    /// the closest of `fields` together with its distance, `None` without any fields. For rays
+    /// that's the first field they hit and for casts the one closest to the axis, see
+    /// [`InputDistance::total_cmp`].
+    pub fn closest_field<'a, T: FieldTransform + 'a>(
+        &self,
+        fields: impl IntoIterator<Item = (Entity, &'a Field, &'a T, &'a SuisRaymarchSettings)>,
+    ) -> Option<(Entity, InputDistance)> {
+        fields
+            .into_iter()
+            .map(|(entity, field, field_transform, settings)| {
+                (
+                    entity,
+                    self.input_distance(field, field_transform, settings),
+                )
+            })
+            .min_by(|(_, d1), (_, d2)| d1.total_cmp(d2))
+    }
+    /// never more than [`SpatialInputData::distance`] for any field inside of `bounds`
+    pub fn bounds_distance(&self, bounds: &Aabb3d) -> f32 {
+        match self {
+            SpatialInputData::Hand(hand) => hand
+                .tips()
+                .map(|tip| bounds.closest_point(tip.pos).distance(tip.pos.into()))
+                .into_iter()
+                .reduce(f32::min)
+                .unwrap(),
+            SpatialInputData::Tip(isometry) => bounds
+                .closest_point(isometry.translation)
+                .distance(isometry.translation),
+            SpatialInputData::Ray(ray) | SpatialInputData::FiniteRay { ray, .. } => {
+                ray_bounds_distance(ray, self.max_ray_length(), bounds)
+            }
+            SpatialInputData::Arc(arc) => arc
+                .segments()
+                .map(|(ray, length)| ray_bounds_distance(&ray, length, bounds))
+                .fold(
+                    bounds.closest_point(arc.origin).distance(arc.origin.into()),
+                    f32::min,
+                ),
+            SpatialInputData::Cast(cast) => cast.bounds_distance(bounds),
+        }
+    }
+    pub fn distance(
+        &self,
+        field: &Field,
+        field_transform: &impl FieldTransform,
+        settings: &SuisRaymarchSettings,
+    ) -> f32 {
         match self {
             SpatialInputData::Hand(hand) => hand.distance(field, field_transform),
             SpatialInputData::Tip(isometry) => {
                 field.distance(field_transform, isometry.translation)
             }
-            SpatialInputData::Ray(ray) => field.raymarch(field_transform, *ray).closest_distance,
+            SpatialInputData::Ray(_)
+            | SpatialInputData::FiniteRay { .. }
+            | SpatialInputData::Arc(_)
+            | SpatialInputData::Cast(_) => {
+                self.raymarch(field, field_transform, settings)
+                    .0
+                    .closest_distance
+            }
+        }
+    }
+    /// like [`SpatialInputData::distance`], but also where a ray, arc or cast hits the field
+    pub fn input_distance(
+        &self,
+        field: &Field,
+        field_transform: &impl FieldTransform,
+        settings: &SuisRaymarchSettings,
+    ) -> InputDistance {
+        match self {
+            SpatialInputData::Hand(_) | SpatialInputData::Tip(_) => InputDistance {
+                distance: self.distance(field, field_transform, settings),
+                hit: None,
+                cast_offset: None,
+            },
+            SpatialInputData::Cast(cast) => {
+                let (result, cast_offset) = cast.raymarch(field, field_transform, settings);
+                InputDistance {
+                    distance: result.closest_distance,
+                    hit: result.hit,
+                    cast_offset: Some(cast_offset),
+                }
+            }
+            _ => {
+                let (result, _) = self.raymarch(field, field_transform, settings);
+                InputDistance {
+                    distance: result.closest_distance,
+                    hit: result.hit,
+                    cast_offset: None,
+                }
+            }
         }
     }
-    pub fn closest_point(&self, field: &Field, field_transform: &GlobalTransform) -> Vec3A {
+    pub fn closest_point(
+        &self,
+        field: &Field,
+        field_transform: &impl FieldTransform,
+        settings: &SuisRaymarchSettings,
+    ) -> Vec3A {
         match self {
             SpatialInputData::Hand(hand) => hand.closest_point(field, field_transform),
             SpatialInputData::Tip(isometry) => {
                 field.closest_point(field_transform, isometry.translation)
             }
-            SpatialInputData::Ray(ray) => field.closest_point(
-                field_transform,
-                ray.get_point(
-                    field
-                        .raymarch(field_transform, *ray)
-                        .deepest_point_ray_length,
-                ),
-            ),
+            SpatialInputData::Ray(_)
+            | SpatialInputData::FiniteRay { .. }
+            | SpatialInputData::Arc(_)
+            | SpatialInputData::Cast(_) => {
+                let (_, deepest_point) = self.raymarch(field, field_transform, settings);
+                field.closest_point(field_transform, deepest_point)
+            }
+        }
+    }
+    /// the coordinates on the field where the input is closest to it, `None` for fields without
+    /// them, see [`Field::surface_coordinates`]
+    pub fn surface_coordinates(
+        &self,
+        field: &Field,
+        field_transform: &impl FieldTransform,
+        settings: &SuisRaymarchSettings,
+    ) -> Option<SurfaceCoordinates> {
+        // finding the closest point can mean sphere tracing, so it's skipped when it isn't needed
+        if !field.has_surface_coordinates() {
+            return None;
         }
+        let point = self.closest_point(field, field_transform, settings);
+        field.surface_coordinates(field_transform, point)
     }
-    pub fn normal(&self, field: &Field, field_transform: &GlobalTransform) -> Dir3A {
+    pub fn normal(
+        &self,
+        field: &Field,
+        field_transform: &impl FieldTransform,
+        settings: &SuisRaymarchSettings,
+    ) -> Dir3A {
         match self {
             SpatialInputData::Hand(hand) => hand.normal(field, field_transform),
             SpatialInputData::Tip(isometry) => {
                 field.normal(field_transform, isometry.translation)
             }
-            SpatialInputData::Ray(ray) => field.normal(
-                field_transform,
-                ray.get_point(
-                    field
-                        .raymarch(field_transform, *ray)
-                        .deepest_point_ray_length,
-                ),
-            ),
+            SpatialInputData::Ray(_)
+            | SpatialInputData::FiniteRay { .. }
+            | SpatialInputData::Arc(_)
+            | SpatialInputData::Cast(_) => {
+                let (_, deepest_point) = self.raymarch(field, field_transform, settings);
+                field.normal(field_transform, deepest_point)
+            }
+        }
+    }
+}
+
+/// the distance between the first `max_length` of the ray and the bounding sphere of `bounds`,
+/// which is cheaper than the box itself
+fn ray_bounds_distance(ray: &Ray3d, max_length: f32, bounds: &Aabb3d) -> f32 {
+    let sphere = bounds.bounding_sphere();
+    let to_center = sphere.center - Vec3A::from(ray.origin);
+    let along = to_center
+        .dot(ray.direction.as_vec3().into())
+        .clamp(0.0, max_length.max(0.0));
+    let offset = to_center - Vec3A::from(ray.direction.as_vec3()) * along;
+    (offset.length() - sphere.radius()).max(0.0)
+}
+
+/// the transformed ray, together with how much longer the transform makes it
+pub(crate) fn transform_ray(ray: Ray3d, mat: &Mat4) -> (Ray3d, f32) {
+    let direction = mat.transform_vector3(ray.direction.as_vec3());
+    let origin = mat.transform_point3(ray.origin);
+    // scale would leave the direction with a non unit length
+    match Dir3::new_and_length(direction) {
+        Ok((direction, scale)) => (Ray3d { origin, direction }, scale),
+        Err(_) => (
+            Ray3d {
+                origin,
+                direction: ray.direction,
+            },
+            1.0,
+        ),
+    }
+}
+
+/// How far an input is from a field, also where a ray, arc or cast hits it
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct InputDistance {
+    /// the closest the input gets to the field, see [`SpatialInputData::distance`]. For casts
+    /// that's how far outside of the cast shape the field is, negative inside of it.
+    pub distance: f32,
+    /// `None` when the ray misses and for inputs without a ray
+    pub hit: Option<RayHit>,
+    /// how far the field is from the axis of a [`ShapeCast`], perpendicular for sphere casts and
+    /// the angle in radians for cone casts, 0 when the axis hits the field. `None` for anything
+    /// but casts.
+    pub cast_offset: Option<f32>,
+}
+
+impl InputDistance {
+    /// how far along the ray or arc the field gets hit first, negative when it starts inside of
+    /// the field
+    pub fn ray_length(&self) -> Option<f32> {
+        self.hit.map(|hit| hit.entry)
+    }
+    /// casts prefer whatever is closest to their axis, otherwise ray hits come first, front to
+    /// back, then everything else nearest first
+    pub fn total_cmp(&self, other: &Self) -> Ordering {
+        if let (Some(o1), Some(o2)) = (self.cast_offset, other.cast_offset) {
+            let by_offset = o1.total_cmp(&o2);
+            if by_offset.is_ne() {
+                return by_offset;
+            }
+        }
+        match (self.ray_length(), other.ray_length()) {
+            (Some(l1), Some(l2)) => l1
+                .total_cmp(&l2)
+                .then_with(|| self.distance.total_cmp(&other.distance)),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => self.distance.total_cmp(&other.distance),
         }
     }
 }
@@ -91,6 +332,25 @@ pub struct InputData {
     pub spatial_data: SpatialInputData,
     pub non_spatial_data: NonSpatialInputData,
     pub handler_location: GlobalTransform,
+    /// the closest field of the handler, see [`FieldRef`](crate::input_handler::FieldRef)
+    pub field: Entity,
+    /// the closest the input gets to the field, see [`InputDistance`]
     pub distance: f32,
+    /// how far along the ray or arc the field gets hit first, `None` when it misses and for other
+    /// inputs
+    pub ray_length: Option<f32>,
+    /// where the ray or arc hits the field in world-space, with the normal of the field there.
+    /// For casts it's where the cast shape first touches the field.
+    pub ray_hit: Option<RayHit>,
+    /// how far the field is from the axis of a cast, see [`InputDistance::cast_offset`]
+    pub cast_offset: Option<f32>,
+    /// where on the field the ray got snapped to in world-space, `None` when it wasn't, see
+    /// [`TargetAssist`](crate::target_assist::TargetAssist)
+    pub snap_point: Option<Vec3A>,
+    /// where on the closest field the input is, for fields like [`Panel`](crate::field::Panel)
+    pub surface_coordinates: Option<SurfaceCoordinates>,
+    /// whether the ray hits an opaque handler before this one, see
+    /// [`RayOcclusion`](crate::ray_occlusion::RayOcclusion)
+    pub occluded: bool,
     pub captured: bool,
 }
@@ -1,31 +1,88 @@
-use bevy::{app::PluginGroupBuilder, prelude::*};
+use bevy::{
+    app::PluginGroupBuilder,
+    prelude::*,
+    transform::systems::{propagate_parent_transforms, sync_simple_transforms},
+};
+use broadphase::{FieldBroadphase, SuisBroadphaseSettings, rebuild_field_broadphase};
+use field::{Field, update_field_caches};
+use handler_actions::{
+    multi::MultiHandlerAction, simple::SimpleHandlerAction, single::SingleHandlerAction,
+};
+use input_handler::InputHandler;
+use input_layers::InputLayers;
+use input_method::InputMethod;
 use input_method_capturing::InputMethodCapturingPlugin;
+use order_helper::{HandlerOrdering, InputPriority, update_handler_orders};
+use ray_occlusion::{InputTransparent, RayOcclusion};
+use raymarching::SuisRaymarchSettings;
 use std::hash::Hash;
+use target_assist::{Magnetism, TargetAssist};
+pub mod broadphase;
+mod bvh;
 pub mod debug;
 pub mod default_input_methods;
 pub mod field;
 pub mod hand;
 pub mod handler_actions;
 pub mod input_handler;
+pub mod input_layers;
 pub mod input_method;
 pub mod input_method_capturing;
 pub mod input_method_data;
 pub mod order_helper;
+pub mod parabolic_arc;
+pub mod ray_occlusion;
+pub mod raymarching;
+pub mod shape_cast;
+pub mod target_assist;
 
 pub struct SuisCorePlugin;
 impl Plugin for SuisCorePlugin {
     fn build(&self, app: &mut App) {
         app.register_disabling_component::<InputMethodDisabled>();
+        app.register_type::<Field>()
+            .register_type::<InputHandler>()
+            .register_type::<InputMethod>()
+            .register_type::<InputLayers>()
+            .register_type::<RayOcclusion>()
+            .register_type::<InputTransparent>()
+            .register_type::<TargetAssist>()
+            .register_type::<Magnetism>()
+            .register_type::<HandlerOrdering>()
+            .register_type::<InputPriority>()
+            .register_type::<SimpleHandlerAction>()
+            .register_type::<MultiHandlerAction>()
+            .register_type::<SingleHandlerAction>();
+        app.init_resource::<SuisRaymarchSettings>();
+        app.init_resource::<SuisBroadphaseSettings>();
+        app.init_resource::<FieldBroadphase>();
         app.configure_sets(
             PreUpdate,
             (
                 SuisPreUpdateSets::PrepareMethodEvents,
                 SuisPreUpdateSets::UpdateInputMethods,
+                SuisPreUpdateSets::OrderHandlers,
                 SuisPreUpdateSets::CaptureInputMethods,
                 SuisPreUpdateSets::SendInputData,
             )
                 .chain(),
         );
+        app.add_systems(
+            PreUpdate,
+            (update_field_caches, rebuild_field_broadphase)
+                .chain()
+                .after(propagate_parent_transforms)
+                .after(sync_simple_transforms)
+                .in_set(SuisPreUpdateSets::PrepareMethodEvents),
+        );
+        app.add_systems(
+            PreUpdate,
+            update_handler_orders.in_set(SuisPreUpdateSets::OrderHandlers),
+        );
+        app.add_systems(
+            PostUpdate,
+            update_field_caches.after(TransformSystem::TransformPropagate),
+        );
     }
 }
 pub struct SuisPlugins;
@@ -61,6 +118,134 @@ pub fn update_input_method_disabled(
 pub enum SuisPreUpdateSets {
     PrepareMethodEvents,
     UpdateInputMethods,
+    /// every input method gets its handler order here, see [`HandlerOrdering`]. It's its own set
+    /// so the order is computed after all of the spatial data is written in
+    /// [`SuisPreUpdateSets::UpdateInputMethods`], custom input methods included.
+    OrderHandlers,
     CaptureInputMethods,
     SendInputData,
 }
+
+#[cfg(test)]
+mod tests {
+    use bevy::{
+        ecs::entity::EntityHashMap,
+        math::Vec3A,
+        scene::{DynamicSceneBuilder, ron, serde::SceneDeserializer},
+    };
+    use serde::de::DeserializeSeed;
+
+    use super::*;
+    use crate::{
+        field::CompositeField,
+        input_handler::FieldRef,
+        input_method_data::{NonSpatialInputData, SpatialInputData},
+    };
+
+    fn app() -> App {
+        let mut app = App::new();
+        app.add_plugins((
+            MinimalPlugins,
+            TransformPlugin,
+            SuisCorePlugin,
+            InputMethodCapturingPlugin,
+        ));
+        app
+    }
+
+    fn update_actions(mut handlers: Query<(&mut InputHandler, &mut SingleHandlerAction)>) {
+        for (mut handler, mut action) in &mut handlers {
+            action.update(
+                &mut handler,
+                true,
+                |_| true,
+                |data| data.non_spatial_data.select > 0.5,
+            );
+        }
+    }
+
+    #[test]
+    fn scene_round_trip() {
+        let mut app = app();
+        app.add_systems(Update, update_actions);
+        let world = app.world_mut();
+        let field = world
+            .spawn((
+                Field::Composite(CompositeField::union([
+                    Field::Sphere(0.5).with_transform(Transform::from_xyz(1.0, 0.0, 0.0)),
+                    Field::custom(Sphere::new(0.5)).with_transform(Transform::IDENTITY),
+                ])),
+                Transform::from_xyz(-1.0, 0.0, -2.0),
+            ))
+            .id();
+        let handler = world
+            .spawn((
+                InputHandler::new(FieldRef::Entity(field)),
+                SingleHandlerAction::default(),
+            ))
+            .id();
+        let method = world
+            .spawn((
+                InputMethod::new(),
+                SpatialInputData::Ray(Ray3d::new(Vec3::ZERO, Dir3::NEG_Z)),
+                NonSpatialInputData::default(),
+            ))
+            .id();
+        // only methods that were already hovering get captured when selecting
+        for _ in 0..3 {
+            app.update();
+        }
+        let mut data = app
+            .world_mut()
+            .get_mut::<NonSpatialInputData>(method)
+            .unwrap();
+        data.select = 1.0;
+        for _ in 0..3 {
+            app.update();
+        }
+        let action = app.world().get::<SingleHandlerAction>(handler).unwrap();
+        assert_eq!(action.actor_entity(), Some(method));
+
+        let registry = app.world().resource::<AppTypeRegistry>().clone();
+        let scene = DynamicSceneBuilder::from_world(app.world())
+            .extract_entities([field, handler, method].into_iter())
+            .build();
+        let serialized = scene.serialize(&registry.read()).unwrap();
+        let mut deserializer = ron::de::Deserializer::from_str(&serialized).unwrap();
+        let scene = SceneDeserializer {
+            type_registry: &registry.read(),
+        }
+        .deserialize(&mut deserializer)
+        .unwrap();
+
+        let mut app = self::app();
+        // the loaded entities shouldn't end up with their old ids by accident
+        app.world_mut()
+            .spawn_batch((0..8).map(|_| Transform::default()));
+        let mut entity_map = EntityHashMap::default();
+        scene
+            .write_to_world(app.world_mut(), &mut entity_map)
+            .unwrap();
+        let [field, handler, method] = [field, handler, method].map(|entity| entity_map[&entity]);
+        app.update();
+
+        let world = app.world();
+        let input_handler = world.get::<InputHandler>(handler).unwrap();
+        assert!(
+            matches!(input_handler.get_field_ref(), FieldRef::Entity(entity) if *entity == field)
+        );
+        let action = world.get::<SingleHandlerAction>(handler).unwrap();
+        assert_eq!(action.actor_entity(), Some(method));
+        let input_method = world.get::<InputMethod>(method).unwrap();
+        assert_eq!(input_method.captured_by(), Some(handler));
+        assert_eq!(input_method.get_handler_order(), &vec![handler]);
+        let Field::Composite(composite) = world.get::<Field>(field).unwrap() else {
+            panic!("the field should still be a composite");
+        };
+        let [sphere, custom] = [0, 1].map(|i| &composite.children[i]);
+        assert_eq!(sphere.transform().translation, Vec3::X);
+        assert!(sphere.local_distance(Vec3A::new(1.5, 0.0, 0.0)).abs() < 1e-5);
+        // custom fields can't be reflected
+        assert_eq!(custom.local_distance(Vec3A::ZERO), f32::INFINITY);
+    }
+}
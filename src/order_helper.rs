@@ -1,38 +1,294 @@
-use bevy::{ecs::system::SystemParam, prelude::*};
+use std::cmp::Ordering;
+
+use bevy::{
+    ecs::{entity::EntityHashSet, system::SystemParam},
+    prelude::*,
+};
 
 use crate::{
-    field::Field,
-    input_handler::{FieldRef, InputHandler},
+    broadphase::{FieldBroadphase, SuisBroadphaseSettings},
+    field::{Field, FieldCache, FieldTransform},
+    input_handler::InputHandler,
+    input_layers::InputLayers,
+    input_method::InputMethod,
+    input_method_data::{InputDistance, SpatialInputData},
+    ray_occlusion::{InputTransparent, RayOcclusion, is_occluded, occluder_ray_length},
+    raymarching::SuisRaymarchSettings,
+    target_assist::{Magnetism, Snap, TargetAssist},
 };
 
+/// How the handler order of this [`InputMethod`] gets sorted, every method gets its order in
+/// [`SuisPreUpdateSets::OrderHandlers`](crate::SuisPreUpdateSets::OrderHandlers). Handlers that
+/// would tie are sorted by entity, so their order stays the same between frames.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Default, Reflect)]
+#[reflect(Component, Debug, Default, PartialEq, Clone)]
+pub enum HandlerOrdering {
+    /// nearest first, even for handlers a ray hits
+    Distance,
+    /// handlers a ray hits first, front to back, then everything else nearest first, see
+    /// [`InputDistance::total_cmp`](crate::input_method_data::InputDistance::total_cmp)
+    #[default]
+    RayDepth,
+    /// like [`HandlerOrdering::RayDepth`], but every point of [`InputPriority`] counts as
+    /// `weight` less ray length, cast offset, snap angle or distance. Handlers a ray hits still
+    /// come before the ones it snaps to and those before the rest.
+    Priority { weight: f32 },
+    /// keeps the order set with [`InputMethod::set_handler_order`], for methods that sort
+    /// handlers themselves
+    Manual,
+}
+
+/// Moves this [`InputHandler`] ahead of handlers that are a bit closer for input methods with
+/// [`HandlerOrdering::Priority`], negative priorities move it back. Handlers without it have a
+/// priority of 0.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default, Reflect)]
+#[reflect(Component, Debug, Default, PartialEq, Clone)]
+pub struct InputPriority(pub i32);
+
+/// How far a handler is from an input method, computed when ordering the handlers and reused
+/// when sending the input data
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct HandlerDistance {
+    pub handler: Entity,
+    /// the closest field of the handler, or the one the input snapped to
+    pub field: Entity,
+    pub distance: InputDistance,
+    pub snap: Option<Snap>,
+    pub priority: InputPriority,
+    pub transparent: bool,
+}
+
+pub(crate) fn update_handler_orders(
+    mut methods: Query<(
+        &mut InputMethod,
+        &SpatialInputData,
+        &InputLayers,
+        Option<&HandlerOrdering>,
+        Option<&RayOcclusion>,
+        Option<&TargetAssist>,
+    )>,
+    handler_query: InputHandlerQueryHelper,
+) {
+    for (mut method, spatial_data, layers, ordering, occlusion, assist) in &mut methods {
+        let ordering = ordering.copied().unwrap_or_default();
+        // manually ordered methods still get their data sent from the distances
+        let distances = handler_query.handler_distances(spatial_data, layers, assist);
+        if ordering != HandlerOrdering::Manual {
+            method.set_handler_order(order_handlers(distances.clone(), ordering, occlusion));
+        }
+        method.set_handler_distances(distances);
+    }
+}
+
+/// the closest of `fields` and the snap for rays that miss all of them, see
+/// [`SpatialInputData::closest_field`] and [`TargetAssist::snap`]
+pub(crate) fn closest_field_with_snap<'a, T: FieldTransform + 'a, I>(
+    spatial_data: &SpatialInputData,
+    assist: Option<&TargetAssist>,
+    magnetism: Option<&Magnetism>,
+    fields: impl Fn() -> I,
+) -> Option<(Entity, InputDistance, Option<Snap>)>
+where
+    I: Iterator<Item = (Entity, &'a Field, &'a T, &'a SuisRaymarchSettings)>,
+{
+    let (field, distance) = spatial_data.closest_field(fields())?;
+    let snap = assist
+        .filter(|_| distance.hit.is_none())
+        .and_then(|assist| assist.snap(spatial_data, magnetism, fields()));
+    // snapped inputs are on the field where they snapped to
+    Some((snap.map_or(field, |snap| snap.field), distance, snap))
+}
+
+impl HandlerDistance {
+    /// hits first, then snapped handlers, then everything else
+    fn rank(&self) -> u8 {
+        match (self.distance.hit, self.snap) {
+            (Some(_), _) => 0,
+            (None, Some(_)) => 1,
+            (None, None) => 2,
+        }
+    }
+    /// handlers the ray hits first, then snapped ones by their weighted angle, then everything
+    /// else, see [`InputDistance::total_cmp`]
+    fn depth_cmp(&self, other: &Self) -> Ordering {
+        self.rank()
+            .cmp(&other.rank())
+            .then_with(|| match (self.rank(), self.snap, other.snap) {
+                (1, Some(s1), Some(s2)) => s1.weighted_angle.total_cmp(&s2.weighted_angle),
+                _ => self.distance.total_cmp(&other.distance),
+            })
+    }
+}
+
+/// what [`HandlerDistance::depth_cmp`] sorts by within the rank of the handler, lowered by its
+/// priority
+fn weighted(handler: &HandlerDistance, weight: f32) -> f32 {
+    let distance = &handler.distance;
+    let key = match (distance.cast_offset, distance.ray_length(), handler.snap) {
+        (Some(offset), ..) => offset,
+        (None, Some(ray_length), _) => ray_length,
+        (None, None, Some(snap)) => snap.weighted_angle,
+        (None, None, None) => distance.distance,
+    };
+    key - handler.priority.0 as f32 * weight
+}
+
+/// sorts `handlers` by `ordering` and leaves out the occluded ones
+fn order_handlers(
+    mut handlers: Vec<HandlerDistance>,
+    ordering: HandlerOrdering,
+    occlusion: Option<&RayOcclusion>,
+) -> Vec<Entity> {
+    handlers.sort_by(|h1, h2| {
+        match ordering {
+            HandlerOrdering::Distance => h1.distance.distance.total_cmp(&h2.distance.distance),
+            HandlerOrdering::RayDepth | HandlerOrdering::Manual => h1.depth_cmp(h2),
+            HandlerOrdering::Priority { weight } => h1
+                .rank()
+                .cmp(&h2.rank())
+                .then_with(|| weighted(h1, weight).total_cmp(&weighted(h2, weight))),
+        }
+        .then_with(|| h1.handler.cmp(&h2.handler))
+    });
+    let occluder_ray_length = occlusion.and_then(|_| {
+        occluder_ray_length(
+            handlers
+                .iter()
+                .map(|handler| (handler.distance.ray_length(), handler.transparent)),
+        )
+    });
+    handlers
+        .into_iter()
+        .filter(|handler| !is_occluded(handler.distance.ray_length(), occluder_ray_length))
+        .map(|handler| handler.handler)
+        .collect()
+}
+
 #[derive(SystemParam)]
 pub struct InputHandlerQueryHelper<'w, 's> {
-    handler_query: Query<'w, 's, (Entity, &'static InputHandler), With<InputHandler>>,
-    field_query: Query<'w, 's, (&'static Field, &'static GlobalTransform)>,
+    handler_query: Query<
+        'w,
+        's,
+        (
+            Entity,
+            &'static InputHandler,
+            &'static InputLayers,
+            Has<InputTransparent>,
+            Option<&'static Magnetism>,
+            Option<&'static InputPriority>,
+        ),
+        With<InputHandler>,
+    >,
+    children: Query<'w, 's, &'static Children>,
+    field_query: Query<
+        'w,
+        's,
+        (
+            &'static Field,
+            &'static FieldCache,
+            Option<&'static SuisRaymarchSettings>,
+        ),
+    >,
+    raymarch_settings: Res<'w, SuisRaymarchSettings>,
+    broadphase: Res<'w, FieldBroadphase>,
+    broadphase_settings: Res<'w, SuisBroadphaseSettings>,
 }
 impl InputHandlerQueryHelper<'_, '_> {
+    /// calls `callback` for every field of every handler, once per field for handlers with
+    /// multiple fields
     pub fn query_all_handler_fields<T>(
         &self,
-        callback: impl Fn((Entity, &Field, &GlobalTransform)) -> T,
+        callback: impl Fn((Entity, &Field, &FieldCache, &SuisRaymarchSettings)) -> T,
     ) -> Vec<T> {
         self.handler_query
             .iter()
-            .map(|(entity, handler)| {
-                (
-                    entity,
-                    match handler.get_field_ref() {
-                        FieldRef::This => entity,
-                        FieldRef::Entity(entity) => entity,
-                    },
-                )
+            .flat_map(|(handler, input_handler, ..)| {
+                input_handler
+                    .get_field_ref()
+                    .entities(handler, &self.children)
+                    .map(move |field| (handler, field))
             })
             .filter_map(|(handler, field)| {
                 self.field_query
                     .get(field)
-                    .map(|(field, transform)| (handler, field, transform))
+                    .map(|(field, transform, settings)| {
+                        let settings = settings.unwrap_or(&self.raymarch_settings);
+                        (handler, field, transform, settings)
+                    })
                     .ok()
             })
             .map(callback)
             .collect::<Vec<T>>()
     }
+    /// the handlers on any of `layers` within the cull distance of [`SuisBroadphaseSettings`],
+    /// sorted by `ordering`. [`HandlerOrdering::Manual`] sorts like [`HandlerOrdering::RayDepth`]
+    /// here. Only handlers with fields that make it through the [`FieldBroadphase`] get their
+    /// exact distance computed. With [`RayOcclusion`] the handlers behind the first opaque one
+    /// the ray hits are left out, so they can't capture the method. With [`TargetAssist`] the
+    /// handlers the ray snaps to come right after the ones it hits.
+    pub fn handler_order(
+        &self,
+        spatial_data: &SpatialInputData,
+        layers: &InputLayers,
+        ordering: HandlerOrdering,
+        occlusion: Option<&RayOcclusion>,
+        assist: Option<&TargetAssist>,
+    ) -> Vec<Entity> {
+        order_handlers(
+            self.handler_distances(spatial_data, layers, assist),
+            ordering,
+            occlusion,
+        )
+    }
+    /// the unsorted handlers of [`InputHandlerQueryHelper::handler_order`], occluded ones included
+    pub(crate) fn handler_distances(
+        &self,
+        spatial_data: &SpatialInputData,
+        layers: &InputLayers,
+        assist: Option<&TargetAssist>,
+    ) -> Vec<HandlerDistance> {
+        let cull_distance = self.broadphase_settings.cull_distance;
+        let candidates = self
+            .broadphase
+            .candidates(|bounds| spatial_data.bounds_distance(bounds), cull_distance)
+            .into_iter()
+            .collect::<EntityHashSet>();
+        self.handler_query
+            .iter()
+            .filter(|(_, _, handler_layers, ..)| handler_layers.intersects(layers))
+            .filter_map(
+                |(handler, input_handler, _, transparent, magnetism, priority)| {
+                    let fields = || {
+                        self.handler_fields(handler, input_handler)
+                            .filter(|(field, ..)| candidates.contains(field))
+                    };
+                    let (field, distance, snap) =
+                        closest_field_with_snap(spatial_data, assist, magnetism, fields)?;
+                    let in_range = distance.distance <= cull_distance || snap.is_some();
+                    in_range.then(|| HandlerDistance {
+                        handler,
+                        field,
+                        distance,
+                        snap,
+                        priority: priority.copied().unwrap_or_default(),
+                        transparent,
+                    })
+                },
+            )
+            .collect()
+    }
+    fn handler_fields<'a>(
+        &'a self,
+        handler: Entity,
+        input_handler: &'a InputHandler,
+    ) -> impl Iterator<Item = (Entity, &'a Field, &'a FieldCache, &'a SuisRaymarchSettings)> {
+        input_handler
+            .get_field_ref()
+            .entities(handler, &self.children)
+            .filter_map(|entity| {
+                let (field, cache, settings) = self.field_query.get(entity).ok()?;
+                let settings = settings.unwrap_or(&self.raymarch_settings);
+                Some((entity, field, cache, settings))
+            })
+    }
 }
@@ -0,0 +1,138 @@
+use bevy::prelude::*;
+
+use crate::{
+    field::{Field, FieldTransform},
+    raymarching::{RayHit, RayMarchResult, SuisRaymarchSettings},
+};
+
+/// The ballistic arc a thrown object follows, for teleport and throw targeting. It gets marched
+/// against fields as a polyline of [`ParabolicArc::SEGMENTS`] segments.
+#[derive(Debug, Clone, Copy, PartialEq, Reflect)]
+#[reflect(Debug, Clone, PartialEq)]
+pub struct ParabolicArc {
+    pub origin: Vec3,
+    /// the launch direction scaled by the launch speed, in units per second
+    pub velocity: Vec3,
+    /// in units per second squared
+    pub gravity: Vec3,
+    /// roughly how long the arc is, measured along the polyline
+    pub max_length: f32,
+}
+
+impl ParabolicArc {
+    /// how many segments of about the same length the arc is split into
+    pub const SEGMENTS: usize = 32;
+
+    /// launched at `speed` along `direction`, with earth gravity along `-Y`
+    pub fn new(origin: Vec3, direction: Dir3, speed: f32, max_length: f32) -> Self {
+        Self {
+            origin,
+            velocity: direction * speed,
+            gravity: Vec3::NEG_Y * 9.81,
+            max_length,
+        }
+    }
+    pub fn with_gravity(mut self, gravity: Vec3) -> Self {
+        self.gravity = gravity;
+        self
+    }
+    /// where a thrown object would be after `time` seconds
+    pub fn position(&self, time: f32) -> Vec3 {
+        self.origin + self.velocity * time + 0.5 * self.gravity * time * time
+    }
+    /// the corners of the polyline, starting at the origin
+    pub fn points(&self) -> impl Iterator<Item = Vec3> {
+        let segment_length = self.max_length.max(0.0) / Self::SEGMENTS as f32;
+        // neither the velocity nor gravity alone can move further than a segment in a step
+        let max_step = (2.0 * segment_length / self.gravity.length()).sqrt();
+        let mut time = 0.0;
+        (0..=Self::SEGMENTS).map(move |i| {
+            if i > 0 {
+                let speed = (self.velocity + self.gravity * time).length();
+                let step = (segment_length / speed).min(max_step);
+                time += if step.is_finite() { step } else { 0.0 };
+            }
+            self.position(time)
+        })
+    }
+    /// every segment of the polyline as a ray together with the length of the segment
+    pub fn segments(&self) -> impl Iterator<Item = (Ray3d, f32)> {
+        self.points()
+            .zip(self.points().skip(1))
+            .filter_map(|(start, end)| {
+                let (direction, length) = Dir3::new_and_length(end - start).ok()?;
+                Some((Ray3d::new(start, direction), length))
+            })
+    }
+    /// the point `length` along the polyline, the end of the arc for anything longer
+    pub fn point_at(&self, length: f32) -> Vec3 {
+        let mut start = 0.0;
+        let mut end = self.origin;
+        for (ray, segment_length) in self.segments() {
+            if length <= start + segment_length {
+                return ray.get_point((length - start).max(0.0));
+            }
+            start += segment_length;
+            end = ray.get_point(segment_length);
+        }
+        end
+    }
+    pub fn transform(self, mat: &Mat4) -> Self {
+        // the path stays the same over time, only the length has to be scaled
+        let scale = mat.determinant().abs().cbrt();
+        Self {
+            origin: mat.transform_point3(self.origin),
+            velocity: mat.transform_vector3(self.velocity),
+            gravity: mat.transform_vector3(self.gravity),
+            max_length: self.max_length * scale,
+        }
+    }
+    /// marches every segment of the polyline, all lengths are measured along the arc. A hit
+    /// starts on the first segment that hits the field and lasts until the arc leaves it again.
+    pub fn raymarch(
+        &self,
+        field: &Field,
+        field_transform: &impl FieldTransform,
+        settings: &SuisRaymarchSettings,
+    ) -> RayMarchResult {
+        let mut result = RayMarchResult::MISS;
+        let mut start = 0.0;
+        for (ray, length) in self.segments() {
+            let max_distance = (settings.max_distance - start).min(length);
+            if max_distance <= 0.0 {
+                break;
+            }
+            let segment_settings = SuisRaymarchSettings {
+                max_distance,
+                ..*settings
+            };
+            let segment = field.raymarch(field_transform, ray, &segment_settings);
+            result.ray_steps += segment.ray_steps;
+            result.ray_lenght += segment.ray_lenght;
+            if segment.closest_distance < result.closest_distance {
+                result.closest_distance = segment.closest_distance;
+                result.deepest_point_ray_length = start + segment.deepest_point_ray_length;
+            }
+            match (&mut result.hit, segment.hit) {
+                (None, Some(hit)) => {
+                    result.hit = Some(RayHit {
+                        entry: start + hit.entry,
+                        exit: start + hit.exit.min(length),
+                        ..hit
+                    });
+                }
+                // still inside of the field since the segment before
+                (Some(hit), Some(segment_hit)) if segment_hit.entry <= 0.0 => {
+                    hit.exit = start + segment_hit.exit.min(length);
+                }
+                _ => {}
+            }
+            start += length;
+            // the arc left the field before the end of this segment
+            if result.hit.is_some_and(|hit| hit.exit < start) {
+                break;
+            }
+        }
+        result
+    }
+}
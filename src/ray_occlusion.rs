@@ -0,0 +1,41 @@
+use bevy::prelude::*;
+
+/// Makes the first opaque [`InputHandler`](crate::input_handler::InputHandler) the ray of this
+/// [`InputMethod`](crate::input_method::InputMethod) hits block it for every handler that gets hit
+/// further along the ray. Occluded handlers can't capture the method, handlers the ray misses are
+/// never occluded. Does nothing for input methods without a ray.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Default, Reflect)]
+#[reflect(Component, Debug, Default, PartialEq, Clone)]
+pub enum RayOcclusion {
+    /// occluded handlers still get input data, with
+    /// [`InputData::occluded`](crate::input_method_data::InputData::occluded) set
+    #[default]
+    Mark,
+    /// occluded handlers don't get any input data from the method
+    Skip,
+}
+
+/// Lets the rays of input methods with [`RayOcclusion`] pass through this handler, it still gets
+/// input and can capture methods
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Default, Reflect)]
+#[reflect(Component, Debug, Default, PartialEq, Clone)]
+pub struct InputTransparent;
+
+/// where along the ray the first handler that isn't [`InputTransparent`] gets hit, from the ray
+/// lengths of every handler together with whether it's transparent
+pub(crate) fn occluder_ray_length(
+    handlers: impl IntoIterator<Item = (Option<f32>, bool)>,
+) -> Option<f32> {
+    handlers
+        .into_iter()
+        .filter(|(_, transparent)| !transparent)
+        .filter_map(|(ray_length, _)| ray_length)
+        .min_by(f32::total_cmp)
+}
+
+/// whether a handler hit at `ray_length` is behind the occluder
+pub(crate) fn is_occluded(ray_length: Option<f32>, occluder_ray_length: Option<f32>) -> bool {
+    ray_length
+        .zip(occluder_ray_length)
+        .is_some_and(|(l, o)| l > o)
+}
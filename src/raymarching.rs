@@ -1,31 +1,191 @@
-use bevy::prelude::*;
+use bevy::{math::Vec3A, prelude::*};
 
-use crate::Field;
+use crate::field::{Field, FieldTransform};
 
+/// Limits for sphere tracing rays through fields without an exact intersection, all in world
+/// units. Insert it on a [`Field`] entity to override the resource for just that field.
+#[derive(Resource, Component, Debug, Clone, Copy, PartialEq)]
+pub struct SuisRaymarchSettings {
+    pub max_steps: u32,
+    /// how close to the surface a step has to land to count as a hit
+    pub hit_epsilon: f32,
+    pub min_step_size: f32,
+    /// also applies to exact intersections, hits further away than this are ignored
+    pub max_distance: f32,
+}
 
-pub fn raymarch_field(
-    ray: Ray3d,
-    field: &Field,
-    field_transform: &GlobalTransform,
-) -> RayMarchResult {
-    let mut result = RayMarchResult {
+impl Default for SuisRaymarchSettings {
+    fn default() -> Self {
+        Self {
+            max_steps: 1000,
+            hit_epsilon: 0.0001,
+            min_step_size: 0.001,
+            max_distance: 10_000.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RayMarchResult {
+    pub closest_distance: f32,
+    pub deepest_point_ray_length: f32,
+    /// how far the ray got sphere traced, `0.0` for fields with an exact intersection
+    pub ray_lenght: f32,
+    pub ray_steps: u32,
+    /// `None` when the ray missed the field
+    pub hit: Option<RayHit>,
+}
+
+/// Where a ray passes through a [`Field`], distances are along the world-space ray, or along the
+/// whole arc for a [`ParabolicArc`](crate::parabolic_arc::ParabolicArc)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RayHit {
+    /// negative when the ray starts inside of the field
+    pub entry: f32,
+    pub exit: f32,
+    /// where the ray enters the field, the ray origin when it starts inside of it
+    pub point: Vec3A,
+    /// faces back towards the ray unless it starts inside of the field
+    pub normal: Dir3A,
+}
+
+impl RayMarchResult {
+    pub(crate) const MISS: Self = Self {
         closest_distance: f32::MAX,
         deepest_point_ray_length: 0.,
         ray_lenght: 0.,
         ray_steps: 0,
+        hit: None,
+    };
+    pub fn is_hit(&self) -> bool {
+        self.hit.is_some()
+    }
+}
+
+/// uses the exact intersection of the field when it has one, sphere traces it otherwise
+pub fn raymarch_field(
+    ray: Ray3d,
+    field: &Field,
+    field_transform: &impl FieldTransform,
+    settings: &SuisRaymarchSettings,
+) -> RayMarchResult {
+    let world_to_local = field_transform.world_to_local();
+    let direction = world_to_local.transform_vector3(ray.direction.as_vec3());
+    // how many local units a world unit along the ray is
+    let scale = direction.length();
+    let distance_scale = field_transform.distance_scale();
+    let Ok(direction) = Dir3::new(direction) else {
+        return RayMarchResult::MISS;
+    };
+    let local_ray = Ray3d {
+        origin: world_to_local.transform_point3(ray.origin),
+        direction,
+    };
+    let (mut result, hit) = match field.local_ray_intersection(local_ray) {
+        Some(intersection) => {
+            // the ray doesn't reach any further, which matters for finite rays
+            let closest = intersection.closest.min(settings.max_distance * scale);
+            (
+                RayMarchResult {
+                    closest_distance: field.local_distance(local_ray.get_point(closest).into()),
+                    deepest_point_ray_length: closest,
+                    ..RayMarchResult::MISS
+                },
+                intersection.hit,
+            )
+        }
+        None => sphere_trace(local_ray, field, settings, scale, distance_scale),
     };
+    // back to world units
+    result.closest_distance *= distance_scale;
+    result.deepest_point_ray_length /= scale;
+    result.ray_lenght /= scale;
+    result.hit = hit
+        .map(|(entry, exit)| (entry / scale, exit / scale))
+        .filter(|(entry, _)| *entry <= settings.max_distance)
+        // the ray doesn't reach any further, like the closest point above
+        .map(|(entry, exit)| (entry, exit.min(settings.max_distance)))
+        .map(|(entry, exit)| {
+            let point = ray.get_point(entry.max(0.0)).into();
+            let normal = field.normal(field_transform, point);
+            // fields without thickness have the same normal on both sides
+            let facing_away = entry >= 0.0 && normal.dot(ray.direction.as_vec3().into()) > 0.0;
+            RayHit {
+                entry,
+                exit,
+                point,
+                normal: if facing_away { -normal } else { normal },
+            }
+        });
+    result
+}
+
+/// marches along a local space ray, also returns where the ray first enters and leaves the field,
+/// all in local units. The entry is negative when the ray starts inside of the field.
+fn sphere_trace(
+    ray: Ray3d,
+    field: &Field,
+    settings: &SuisRaymarchSettings,
+    scale: f32,
+    distance_scale: f32,
+) -> (RayMarchResult, Option<(f32, f32)>) {
+    let hit_epsilon = settings.hit_epsilon / distance_scale;
+    let min_step_size = settings.min_step_size * scale;
+    let max_distance = settings.max_distance * scale;
+    let mut result = RayMarchResult::MISS;
+    let mut entry = None;
 
-    while result.ray_steps < RAYMARCH_MAX_STEPS && result.ray_lenght < RAYMARCH_MAX_DISTANCE {
-        let point = ray.origin + (ray.direction.as_vec3() * result.ray_lenght);
-        let distance = field.distance(field_transform, point);
+    while result.ray_steps < settings.max_steps {
+        let distance = field.local_distance(ray.get_point(result.ray_lenght).into());
+        result.ray_steps += 1;
         if distance < result.closest_distance {
             result.closest_distance = distance;
             result.deepest_point_ray_length = result.ray_lenght;
         }
-        // max_distance isn't meant for this but it doesn't make sense to march further than the
-        // limit
-        result.ray_lenght += distance.max(RAYMARCH_MIN_STEP_SIZE);
+        match entry {
+            // negative like for exact intersections when starting inside of the field
+            None if distance < 0.0 && result.ray_lenght == 0.0 => {
+                entry = Some(-trace_back(ray, field, settings, &mut result, hit_epsilon, scale));
+            }
+            None if distance <= hit_epsilon => entry = Some(result.ray_lenght),
+            // only the first time the ray passes through the field counts
+            Some(entry) if distance > hit_epsilon => {
+                return (result, Some((entry, result.ray_lenght)));
+            }
+            _ => {}
+        }
+        if result.ray_lenght >= max_distance {
+            break;
+        }
+        // the surface is at least `distance` away in either direction, so this can't skip over it.
+        // The last step stops at the max distance, so the end of a finite ray still counts.
+        result.ray_lenght =
+            (result.ray_lenght + distance.abs().max(min_step_size)).min(max_distance);
     }
 
-    result
+    (result, entry.map(|entry| (entry, result.ray_lenght)))
+}
+
+/// how far behind its origin the ray enters the field, for rays that start inside of it. Marches
+/// backwards until it's out of the field again, in local units.
+fn trace_back(
+    ray: Ray3d,
+    field: &Field,
+    settings: &SuisRaymarchSettings,
+    result: &mut RayMarchResult,
+    hit_epsilon: f32,
+    scale: f32,
+) -> f32 {
+    let min_step_size = settings.min_step_size * scale;
+    let max_distance = settings.max_distance * scale;
+    let mut length = 0.0;
+    while result.ray_steps < settings.max_steps && length < max_distance {
+        let distance = field.local_distance(ray.get_point(-length).into());
+        result.ray_steps += 1;
+        if distance >= -hit_epsilon {
+            break;
+        }
+        length = (length + distance.abs().max(min_step_size)).min(max_distance);
+    }
+    length
 }
@@ -0,0 +1,175 @@
+use bevy::{
+    math::{Vec3A, bounding::Aabb3d},
+    prelude::*,
+};
+
+use crate::{
+    field::{Field, FieldTransform, golden_section_min},
+    input_method_data::transform_ray,
+    raymarching::{RayHit, RayMarchResult, SuisRaymarchSettings},
+};
+
+/// A ray with some leeway around it, which makes small targets easier to hit with shaky input.
+/// Everything within the shape counts as hit, the closer to the axis the better.
+#[derive(Debug, Clone, Copy, PartialEq, Reflect)]
+#[reflect(Debug, Clone, PartialEq)]
+pub struct ShapeCast {
+    pub ray: Ray3d,
+    pub shape: CastShape,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Reflect)]
+#[reflect(Debug, Clone, PartialEq)]
+pub enum CastShape {
+    /// a sphere with this radius moved along the ray, so the same leeway at any distance
+    Sphere(f32),
+    /// a cone around the ray with this angular radius in radians, so the leeway grows with the
+    /// distance. Cones of 45° or wider never get culled, see [`ShapeCast::bounds_distance`].
+    Cone(f32),
+}
+
+impl ShapeCast {
+    pub fn sphere(ray: Ray3d, radius: f32) -> Self {
+        Self {
+            ray,
+            shape: CastShape::Sphere(radius),
+        }
+    }
+    pub fn cone(ray: Ray3d, angle: f32) -> Self {
+        Self {
+            ray,
+            shape: CastShape::Cone(angle),
+        }
+    }
+    /// how far the shape reaches from the axis at `length` along the ray
+    pub fn radius_at(&self, length: f32) -> f32 {
+        match self.shape {
+            CastShape::Sphere(radius) => radius.max(0.0),
+            CastShape::Cone(_) => length.max(0.0) * self.spread(),
+        }
+    }
+    /// how much the radius grows per unit along the ray
+    fn spread(&self) -> f32 {
+        match self.shape {
+            CastShape::Sphere(_) => 0.0,
+            CastShape::Cone(angle) => angle.clamp(0.0, MAX_CONE_ANGLE).tan(),
+        }
+    }
+    /// how far off the axis something `distance` away from the point `length` along the ray is,
+    /// perpendicular for spheres and as an angle for cones
+    fn axis_offset(&self, distance: f32, length: f32) -> f32 {
+        match self.shape {
+            CastShape::Sphere(_) => distance.max(0.0),
+            CastShape::Cone(_) => distance.max(0.0).atan2(length.max(0.0)),
+        }
+    }
+    pub fn transform(self, mat: &Mat4) -> Self {
+        let (ray, scale) = transform_ray(self.ray, mat);
+        let shape = match self.shape {
+            CastShape::Sphere(radius) => CastShape::Sphere(radius * scale),
+            cone => cone,
+        };
+        Self { ray, shape }
+    }
+    /// never more than the closest distance of [`ShapeCast::raymarch`] for any field inside of
+    /// `bounds`, using the bounding sphere
+    pub fn bounds_distance(&self, bounds: &Aabb3d) -> f32 {
+        let sphere = bounds.bounding_sphere();
+        let to_center = sphere.center - Vec3A::from(self.ray.origin);
+        let along = to_center.dot(self.ray.direction.as_vec3().into());
+        let offset = (to_center.length_squared() - along * along).max(0.0).sqrt();
+        let spread = self.spread();
+        // the radius grows at least as fast as the distance to anything behind the sphere
+        if spread >= 1.0 {
+            return f32::NEG_INFINITY;
+        }
+        // where the distance to the center minus the radius of the shape is smallest
+        let slope = (1.0 - spread * spread).sqrt();
+        let distance = if along + spread * offset / slope < 0.0 {
+            to_center.length() - self.radius_at(0.0)
+        } else {
+            offset * slope - along * spread - self.radius_at(0.0)
+        };
+        distance - sphere.radius()
+    }
+    /// sphere traces the shape along the ray, the closest distance is how far outside of the
+    /// shape the field is, negative inside of it. Also returns the smallest offset of the field
+    /// from the axis, see
+    /// [`InputDistance::cast_offset`](crate::input_method_data::InputDistance::cast_offset).
+    pub fn raymarch(
+        &self,
+        field: &Field,
+        field_transform: &impl FieldTransform,
+        settings: &SuisRaymarchSettings,
+    ) -> (RayMarchResult, f32) {
+        let spread = self.spread();
+        let mut result = RayMarchResult::MISS;
+        let mut axis_offset = f32::INFINITY;
+        // the samples before and after the one with the smallest offset
+        let mut around_offset = (0.0, 0.0);
+        let mut previous_length = 0.0;
+        let mut previous_was_offset = false;
+        let mut entry = None;
+        let mut exit = None;
+        while result.ray_steps < settings.max_steps {
+            let length = result.ray_lenght;
+            let distance = field.distance(field_transform, self.ray.get_point(length));
+            let outside = distance - self.radius_at(length);
+            result.ray_steps += 1;
+            if previous_was_offset {
+                around_offset.1 = length;
+            }
+            let offset = self.axis_offset(distance, length);
+            previous_was_offset = offset < axis_offset;
+            if previous_was_offset {
+                axis_offset = offset;
+                around_offset = (previous_length, length);
+            }
+            previous_length = length;
+            if outside < result.closest_distance {
+                result.closest_distance = outside;
+                result.deepest_point_ray_length = length;
+            }
+            match entry {
+                None if outside <= settings.hit_epsilon => entry = Some(length),
+                // only the first time the shape passes through the field counts
+                Some(_) if outside > settings.hit_epsilon => {
+                    exit = Some(length);
+                    break;
+                }
+                _ => {}
+            }
+            if length >= settings.max_distance {
+                break;
+            }
+            // neither the distance nor the radius can change faster than this along the ray
+            let step = (outside.abs() / (1.0 + spread)).max(settings.min_step_size);
+            result.ray_lenght = (length + step).min(settings.max_distance);
+        }
+        result.hit = entry.map(|entry| {
+            let touching = self.ray.get_point(entry);
+            RayHit {
+                entry,
+                exit: exit.unwrap_or(result.ray_lenght),
+                point: field.closest_point(field_transform, touching),
+                normal: field.normal(field_transform, touching),
+            }
+        });
+        // the steps only get small close to the edge of the shape, so the samples can miss the
+        // axis passing through the field or where it passes closest
+        if result.is_hit() && field.raymarch(field_transform, self.ray, settings).is_hit() {
+            axis_offset = 0.0;
+        } else if axis_offset > 0.0 {
+            let (start, end) = around_offset;
+            let offset_at = |length| {
+                let distance = field.distance(field_transform, self.ray.get_point(length));
+                self.axis_offset(distance, length)
+            };
+            axis_offset = axis_offset.min(offset_at(golden_section_min(offset_at, start, end)));
+        }
+        (result, axis_offset)
+    }
+}
+
+/// wider cones would reach behind the origin of the ray
+const MAX_CONE_ANGLE: f32 = std::f32::consts::FRAC_PI_2 - 0.001;
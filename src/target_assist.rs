@@ -0,0 +1,88 @@
+use bevy::{math::Vec3A, prelude::*};
+
+use crate::{
+    field::{Field, FieldTransform},
+    input_method_data::SpatialInputData,
+    raymarching::SuisRaymarchSettings,
+    shape_cast::ShapeCast,
+};
+
+/// Snaps the ray of this [`InputMethod`](crate::input_method::InputMethod) to handlers it misses
+/// by less than `angle`, which makes small targets usable with shaky rays. Handlers the ray hits
+/// still come first in the handler order, snapped ones come before the rest, the closest to the
+/// ray first. Does nothing for input methods without a ray.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Reflect)]
+#[reflect(Component, Debug, Default, PartialEq, Clone)]
+pub struct TargetAssist {
+    /// in radians, measured from the origin of the ray
+    pub angle: f32,
+}
+
+impl Default for TargetAssist {
+    fn default() -> Self {
+        Self {
+            angle: 3f32.to_radians(),
+        }
+    }
+}
+
+/// How strongly this [`InputHandler`](crate::input_handler::InputHandler) pulls in rays with
+/// [`TargetAssist`], scales the angle they snap within and their offset when ordering snapped
+/// handlers. Handlers without it have a magnetism of 1, 0 never snaps.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Reflect)]
+#[reflect(Component, Debug, Default, PartialEq, Clone)]
+pub struct Magnetism(pub f32);
+
+impl Default for Magnetism {
+    fn default() -> Self {
+        Self(1.0)
+    }
+}
+
+/// Where a ray with [`TargetAssist`] that misses a handler gets snapped to
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Snap {
+    pub field: Entity,
+    /// the point on the field closest to the ray, in world-space
+    pub point: Vec3A,
+    /// the angle between the ray and the field divided by the [`Magnetism`] of the handler, the
+    /// lowest one comes first in the handler order
+    pub weighted_angle: f32,
+}
+
+impl TargetAssist {
+    /// the field of `fields` closest to the ray within the angle, `None` when there isn't one or
+    /// `spatial_data` isn't a ray. Only makes sense for handlers the ray misses.
+    pub fn snap<'a, T: FieldTransform + 'a>(
+        &self,
+        spatial_data: &SpatialInputData,
+        magnetism: Option<&Magnetism>,
+        fields: impl IntoIterator<Item = (Entity, &'a Field, &'a T, &'a SuisRaymarchSettings)>,
+    ) -> Option<Snap> {
+        let (SpatialInputData::Ray(ray) | SpatialInputData::FiniteRay { ray, .. }) = spatial_data
+        else {
+            return None;
+        };
+        let magnetism = magnetism.copied().unwrap_or_default().0;
+        if magnetism <= 0.0 {
+            return None;
+        }
+        let cone = ShapeCast::cone(*ray, self.angle * magnetism);
+        fields
+            .into_iter()
+            .filter_map(|(entity, field, field_transform, settings)| {
+                let (result, angle) = cone.raymarch(field, field_transform, settings);
+                // finite rays don't snap to anything past their end
+                let hit = result.hit?;
+                (hit.entry <= spatial_data.max_ray_length()).then(|| {
+                    let closest = ray.get_point(result.deepest_point_ray_length);
+                    Snap {
+                        field: entity,
+                        point: field.closest_point(field_transform, closest),
+                        weighted_angle: angle / magnetism,
+                    }
+                })
+            })
+            .min_by(|s1, s2| s1.weighted_angle.total_cmp(&s2.weighted_angle))
+    }
+}